@@ -0,0 +1,228 @@
+use std::{
+    fs::File,
+    io::{Read, copy},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cache::sha256,
+    error::{InstallerError, io_error},
+};
+
+/// The version of the bundle format
+const FORMAT: u32 = 1;
+
+/// The name of the manifest in the archive
+const MANIFEST: &str = "manifest.json";
+
+/// The name of the checksums file in the archive
+const CHECKSUMS: &str = "SHA256SUMS";
+
+/// A portable archive of Biome binaries for offline deployment
+///
+/// A bundle is a gzipped tarball holding a manifest, a `SHA256SUMS` file
+/// compatible with `sha256sum --check`, and the binaries themselves:
+///
+/// ```text
+/// manifest.json
+/// SHA256SUMS
+/// <version>/<asset>
+/// ```
+pub struct Bundle {
+    path: PathBuf,
+    manifest: Manifest,
+}
+
+/// The description of the content of a bundle
+#[derive(Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: u32,
+    pub created_by: String,
+    pub assets: Vec<BundleEntry>,
+}
+
+/// A binary stored in a bundle
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BundleEntry {
+    pub version: Version,
+    pub asset: String,
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+}
+
+impl Bundle {
+    /// Creates a bundle at the specified path from the specified binaries
+    ///
+    /// Each binary is described by its version, asset name and the path of
+    /// the file holding it.
+    pub fn create(path: &Path, binaries: &[(Version, String, PathBuf)]) -> Result<Manifest> {
+        let mut assets = Vec::new();
+
+        for (version, asset, file) in binaries {
+            assets.push(BundleEntry {
+                version: version.clone(),
+                asset: asset.clone(),
+                path: format!("{version}/{asset}"),
+                sha256: sha256(file)?,
+                size: file.metadata().map_err(io_error(file))?.len(),
+            });
+        }
+
+        let manifest = Manifest {
+            format: FORMAT,
+            created_by: format!("biome-installer {}", env!("CARGO_PKG_VERSION")),
+            assets,
+        };
+
+        let checksums: String = manifest
+            .assets
+            .iter()
+            .map(|entry| format!("{}  {}\n", entry.sha256, entry.path))
+            .collect();
+
+        let archive = File::create(path).map_err(io_error(path))?;
+        let mut builder = tar::Builder::new(GzEncoder::new(archive, Compression::default()));
+
+        append_data(
+            &mut builder,
+            MANIFEST,
+            &serde_json::to_vec_pretty(&manifest)?,
+        )?;
+        append_data(&mut builder, CHECKSUMS, checksums.as_bytes())?;
+
+        for ((_, _, file), entry) in binaries.iter().zip(&manifest.assets) {
+            builder
+                .append_path_with_name(file, &entry.path)
+                .with_context(|| format!("Could not add {} to the bundle", entry.path))?;
+        }
+
+        builder
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .context("Could not write the bundle")?;
+
+        Ok(manifest)
+    }
+
+    /// Opens the bundle at the specified path, and reads its manifest
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut archive = open_archive(path)?;
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+
+            if entry.path()?.as_os_str() == MANIFEST {
+                let mut content = Vec::new();
+                entry.read_to_end(&mut content)?;
+
+                let manifest: Manifest = serde_json::from_slice(&content)
+                    .context("Could not parse the manifest of the bundle")?;
+
+                if manifest.format != FORMAT {
+                    bail!("Unsupported bundle format: {}", manifest.format);
+                }
+
+                return Ok(Bundle {
+                    path: path.to_path_buf(),
+                    manifest,
+                });
+            }
+        }
+
+        bail!("{} is not a Biome bundle", path.display())
+    }
+
+    /// Returns the path of the bundle
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Finds the specified asset in the bundle
+    ///
+    /// If no version is specified, the most recent version of the asset is
+    /// returned.
+    pub fn find(&self, version: Option<&Version>, asset: &str) -> Option<&BundleEntry> {
+        self.manifest
+            .assets
+            .iter()
+            .filter(|entry| entry.asset == asset)
+            .filter(|entry| version.is_none_or(|version| &entry.version == version))
+            .max_by(|a, b| a.version.cmp(&b.version))
+    }
+
+    /// Extracts a binary from the bundle into a temporary file in the
+    /// specified directory, and returns the path to that file
+    ///
+    /// The checksum of the extracted binary is verified against the manifest.
+    pub fn extract(&self, entry: &BundleEntry, dir: &Path) -> Result<PathBuf> {
+        let mut archive = open_archive(&self.path)?;
+
+        for file in archive.entries()? {
+            let mut file = file?;
+
+            if file.path()?.to_string_lossy() != entry.path {
+                continue;
+            }
+
+            let mut temp_file = tempfile::Builder::new()
+                .prefix(".biome-")
+                .tempfile_in(dir)
+                .map_err(io_error(dir))
+                .context("Could not create temporary file")?;
+
+            copy(&mut file, temp_file.as_file_mut())
+                .and_then(|_| temp_file.as_file().sync_all())
+                .context("Could not extract the binary from the bundle")?;
+
+            let actual = sha256(temp_file.path())?;
+
+            if actual != entry.sha256 {
+                return Err(InstallerError::ChecksumMismatch {
+                    file: format!("{}#{}", self.path.display(), entry.path),
+                    expected: entry.sha256.clone(),
+                    actual,
+                }
+                .into());
+            }
+
+            let (_, path) = temp_file
+                .keep()
+                .context("Could not persist temporary file")?;
+
+            return Ok(path);
+        }
+
+        bail!("{} is missing from the bundle", entry.path)
+    }
+}
+
+/// Opens a bundle for reading
+fn open_archive(path: &Path) -> Result<tar::Archive<GzDecoder<File>>> {
+    let file = File::open(path)
+        .map_err(io_error(path))
+        .with_context(|| format!("Could not open the bundle {}", path.display()))?;
+
+    Ok(tar::Archive::new(GzDecoder::new(file)))
+}
+
+/// Adds a file with the specified content to an archive
+fn append_data<W: std::io::Write>(
+    builder: &mut tar::Builder<W>,
+    path: &str,
+    content: &[u8],
+) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+
+    builder
+        .append_data(&mut header, path, content)
+        .with_context(|| format!("Could not add {path} to the bundle"))
+}
@@ -0,0 +1,288 @@
+use std::{
+    env::consts::OS,
+    fs::{File, create_dir_all, read_dir, read_to_string, remove_dir_all, remove_file, write},
+    io::{ErrorKind, Write, copy},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use home::home_dir;
+use semver::Version;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::{InstallerError, io_error};
+
+/// Local cache of downloaded assets and version lists
+///
+/// Assets are stored by version and asset name, next to a file holding
+/// their SHA-256 checksum, which is verified every time they are reused.
+/// The version lists of our version API are stored as they were fetched:
+///
+/// ```text
+/// <root>/assets/<version>/<asset>
+/// <root>/assets/<version>/<asset>.sha256
+/// <root>/versions/<list>
+/// ```
+pub struct Cache {
+    root: PathBuf,
+}
+
+/// An asset stored in the cache
+#[derive(Serialize)]
+pub struct CacheEntry {
+    pub version: Version,
+    pub asset: String,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+impl Cache {
+    /// Opens the cache in the specified directory
+    pub fn new(root: PathBuf) -> Self {
+        Cache { root }
+    }
+
+    /// Returns the default cache directory for the current platform
+    ///
+    /// This is `$XDG_CACHE_HOME/biome-installer` (or `~/.cache/biome-installer`)
+    /// on Linux, `~/Library/Caches/biome-installer` on macOS, and
+    /// `%LOCALAPPDATA%\biome-installer\cache` on Windows.
+    pub fn default_dir() -> Result<PathBuf> {
+        let home = home_dir().context("Could not determine the home directory")?;
+
+        let dir = match OS {
+            "macos" => home.join("Library/Caches/biome-installer"),
+            "windows" => std::env::var_os("LOCALAPPDATA")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.join("AppData/Local"))
+                .join("biome-installer")
+                .join("cache"),
+            _ => std::env::var_os("XDG_CACHE_HOME")
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute())
+                .unwrap_or_else(|| home.join(".cache"))
+                .join("biome-installer"),
+        };
+
+        Ok(dir)
+    }
+
+    /// Returns the root directory of the cache
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the cached asset for the specified version, if any
+    ///
+    /// The checksum of the asset is verified before it is returned. If it
+    /// doesn't match, a [InstallerError::ChecksumMismatch] error is returned.
+    pub fn get(&self, version: &Version, asset: &str) -> Result<Option<PathBuf>> {
+        let path = self.asset_path(version, asset);
+
+        let Ok(expected) = read_to_string(checksum_path(&path)) else {
+            return Ok(None);
+        };
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let actual = sha256(&path)?;
+
+        if actual != expected.trim() {
+            return Err(InstallerError::ChecksumMismatch {
+                file: path.display().to_string(),
+                expected: expected.trim().to_string(),
+                actual,
+            }
+            .into());
+        }
+
+        Ok(Some(path))
+    }
+
+    /// Stores a copy of the asset for the specified version
+    pub fn store(&self, version: &Version, asset: &str, source: &Path) -> Result<()> {
+        let path = self.asset_path(version, asset);
+        let dir = path.parent().context("Invalid cache path")?;
+
+        create_dir_all(dir)
+            .map_err(io_error(dir))
+            .context("Could not create the cache directory")?;
+
+        let mut staged = tempfile::Builder::new()
+            .prefix(".biome-")
+            .tempfile_in(dir)
+            .map_err(io_error(dir))?;
+
+        copy(&mut File::open(source)?, staged.as_file_mut())?;
+        staged.as_file().sync_all()?;
+
+        // The checksum is removed first and written last, so that an
+        // interrupted write is treated as a cache miss
+        let _ = remove_file(checksum_path(&path));
+
+        staged
+            .persist(&path)
+            .map_err(|err| io_error(&path)(err.error))?;
+
+        write(checksum_path(&path), sha256(&path)?).map_err(io_error(&path))?;
+
+        Ok(())
+    }
+
+    /// Removes the cached asset for the specified version
+    pub fn remove(&self, version: &Version, asset: &str) -> Result<()> {
+        let path = self.asset_path(version, asset);
+
+        for file in [checksum_path(&path), path] {
+            if file.exists() {
+                remove_file(&file).map_err(io_error(&file))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the cached version list with the specified name, if any
+    pub fn version_list(&self, name: &str) -> Result<Option<String>> {
+        let path = self.root.join("versions").join(name);
+
+        match read_to_string(&path) {
+            Ok(list) => Ok(Some(list)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(io_error(&path)(err)),
+        }
+    }
+
+    /// Stores the version list with the specified name
+    pub fn store_version_list(&self, name: &str, list: &str) -> Result<()> {
+        let dir = self.root.join("versions");
+
+        create_dir_all(&dir)
+            .map_err(io_error(&dir))
+            .context("Could not create the cache directory")?;
+
+        let mut staged = tempfile::Builder::new()
+            .prefix(".versions-")
+            .tempfile_in(&dir)
+            .map_err(io_error(&dir))?;
+
+        staged.write_all(list.as_bytes())?;
+
+        let path = dir.join(name);
+
+        staged
+            .persist(&path)
+            .map_err(|err| io_error(&path)(err.error))?;
+
+        Ok(())
+    }
+
+    /// Lists the cached assets, from the most recent version to the oldest
+    pub fn entries(&self) -> Result<Vec<CacheEntry>> {
+        let assets_dir = self.root.join("assets");
+
+        if !assets_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::new();
+
+        for version_dir in read_dir(&assets_dir).map_err(io_error(&assets_dir))? {
+            let version_dir = version_dir?.path();
+
+            let Some(version) = version_dir
+                .file_name()
+                .and_then(|name| Version::parse(&name.to_string_lossy()).ok())
+            else {
+                continue;
+            };
+
+            for file in read_dir(&version_dir).map_err(io_error(&version_dir))? {
+                let file = file?;
+                let asset = file.file_name().to_string_lossy().to_string();
+
+                if asset.starts_with('.') || asset.ends_with(".sha256") {
+                    continue;
+                }
+
+                entries.push(CacheEntry {
+                    version: version.clone(),
+                    asset,
+                    path: file.path(),
+                    size: file.metadata()?.len(),
+                });
+            }
+        }
+
+        entries.sort_by(|a, b| b.version.cmp(&a.version).then(a.asset.cmp(&b.asset)));
+
+        Ok(entries)
+    }
+
+    /// Removes every cached asset and returns them
+    pub fn clean(&self) -> Result<Vec<CacheEntry>> {
+        let entries = self.entries()?;
+        let assets_dir = self.root.join("assets");
+
+        if assets_dir.exists() {
+            remove_dir_all(&assets_dir)
+                .map_err(io_error(&assets_dir))
+                .context("Could not remove the cached assets")?;
+        }
+
+        Ok(entries)
+    }
+
+    /// Removes the cached assets of all but the `keep` most recent versions
+    /// and returns them
+    pub fn prune(&self, keep: usize) -> Result<Vec<CacheEntry>> {
+        let entries = self.entries()?;
+
+        let mut versions: Vec<&Version> = entries.iter().map(|entry| &entry.version).collect();
+        versions.dedup();
+
+        let stale: Vec<Version> = versions.into_iter().skip(keep).cloned().collect();
+
+        for version in &stale {
+            let dir = self.root.join("assets").join(version.to_string());
+
+            remove_dir_all(&dir)
+                .map_err(io_error(&dir))
+                .context("Could not remove the cached assets")?;
+        }
+
+        Ok(entries
+            .into_iter()
+            .filter(|entry| stale.contains(&entry.version))
+            .collect())
+    }
+
+    /// Computes the path of an asset in the cache
+    fn asset_path(&self, version: &Version, asset: &str) -> PathBuf {
+        self.root
+            .join("assets")
+            .join(version.to_string())
+            .join(asset)
+    }
+}
+
+/// Computes the path of the file holding the checksum of an asset
+fn checksum_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".sha256");
+
+    PathBuf::from(name)
+}
+
+/// Computes the SHA-256 checksum of a file, as a hex string
+pub fn sha256(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+
+    copy(&mut File::open(path).map_err(io_error(path))?, &mut hasher)
+        .context("Could not compute the checksum")?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
@@ -0,0 +1,155 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueHint, builder::PossibleValuesParser, value_parser};
+use colored::Colorize;
+use semver::Version;
+use serde::Serialize;
+
+use crate::{
+    bundle::{Bundle, BundleEntry},
+    cache::Cache,
+    config::Config,
+    downloader::{ASSET_NAMES, Downloader},
+    http::HttpOptions,
+    output::Output,
+};
+
+#[derive(Args, Clone, Debug)]
+pub struct BundleCommand {
+    #[arg(
+        short,
+        long = "version",
+        value_name = "VERSION",
+        required = true,
+        value_delimiter = ',',
+        value_hint = ValueHint::Other,
+        value_parser = value_parser!(Version),
+        help = "The versions of Biome to include in the bundle",
+    )]
+    versions: Vec<Version>,
+
+    #[arg(
+        long = "target",
+        value_name = "ASSET",
+        value_delimiter = ',',
+        value_parser = PossibleValuesParser::new(ASSET_NAMES),
+        help = "The binaries to include for each version [default: all]",
+    )]
+    targets: Vec<String>,
+
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        default_value = "biome-bundle.tar.gz",
+        value_hint = ValueHint::FilePath,
+        value_parser = value_parser!(PathBuf),
+        help = "The path of the bundle to create",
+    )]
+    file: PathBuf,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        env = "BIOME_CACHE_DIR",
+        value_hint = ValueHint::DirPath,
+        value_parser = value_parser!(PathBuf),
+        help = "The directory in which downloaded binaries are cached",
+    )]
+    cache_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "URL",
+        env = "BIOME_DOWNLOAD_MIRROR",
+        value_hint = ValueHint::Url,
+        help = "The base URL from which to download binaries, instead of GitHub releases",
+    )]
+    download_mirror: Option<String>,
+
+    #[command(flatten)]
+    http: HttpOptions,
+}
+
+/// The bundle that was created
+#[derive(Serialize)]
+struct BundleReport {
+    file: PathBuf,
+    assets: Vec<BundleEntry>,
+}
+
+impl BundleCommand {
+    /// Fills the values that were not specified with those of the
+    /// configuration files
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.http = self.http.with_config(&config);
+        self.download_mirror = self.download_mirror.or(config.download_mirror);
+        self
+    }
+
+    pub fn handle(&self, output: &Output) -> Result<()> {
+        let cache = Cache::new(match &self.cache_dir {
+            Some(dir) => dir.to_owned(),
+            None => Cache::default_dir()?,
+        });
+
+        if let Some(source) = self.http.insecure_source() {
+            output.warn(format!(
+                "TLS certificates will not be verified, as requested {source}."
+            ));
+        }
+
+        let mut downloader = Downloader::new(self.http.client()?).with_cache(cache);
+
+        if let Some(mirror) = &self.download_mirror {
+            downloader = downloader.with_mirror(mirror);
+        }
+
+        let targets: Vec<&str> = match self.targets.is_empty() {
+            true => ASSET_NAMES.to_vec(),
+            false => self.targets.iter().map(String::as_str).collect(),
+        };
+
+        let staging = tempfile::tempdir().context("Could not create temporary directory")?;
+        let mut binaries = Vec::new();
+
+        for version in &self.versions {
+            for asset in &targets {
+                let spinner = output.spinner(format!("Downloading {asset} {version}"));
+
+                let download = downloader
+                    .download_asset(version.clone(), asset, staging.path())
+                    .with_context(|| format!("Failed to download {asset} {version}"))?;
+
+                match download.cached {
+                    true => spinner.success(format!("Using cached {asset} {version}")),
+                    false => spinner.success(format!("Downloaded {asset} {version}")),
+                }
+
+                binaries.push((version.clone(), asset.to_string(), download.path));
+            }
+        }
+
+        let manifest =
+            Bundle::create(&self.file, &binaries).context("Failed to create the bundle")?;
+
+        output.print(
+            format!(
+                "✔ Bundled {} binaries into {}",
+                manifest.assets.len(),
+                format!("{}", self.file.display()).bold()
+            )
+            .green(),
+        );
+        output.print(format_args!(
+            "Run {} on the target machine to install Biome from it.",
+            format!("biome-installer install --bundle {}", self.file.display()).bold()
+        ));
+
+        output.report(&BundleReport {
+            file: self.file.clone(),
+            assets: manifest.assets,
+        })
+    }
+}
@@ -0,0 +1,137 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Args, Subcommand, ValueHint, value_parser};
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::{
+    cache::{Cache, CacheEntry},
+    output::Output,
+};
+
+#[derive(Args, Clone, Debug)]
+pub struct CacheCommand {
+    #[command(subcommand)]
+    command: CacheSubcommand,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        env = "BIOME_CACHE_DIR",
+        value_hint = ValueHint::DirPath,
+        value_parser = value_parser!(PathBuf),
+        global = true,
+        help = "The directory in which downloaded binaries are cached",
+    )]
+    cache_dir: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum CacheSubcommand {
+    /// List the cached binaries
+    List,
+
+    /// Remove every cached binary
+    Clean,
+
+    /// Remove the cached binaries of all but the most recent versions
+    Prune {
+        #[arg(
+            long,
+            value_name = "N",
+            help = "The number of most recent versions to keep"
+        )]
+        keep: usize,
+    },
+}
+
+/// The content of the cache
+#[derive(Serialize)]
+struct CacheListReport {
+    cache_dir: PathBuf,
+    entries: Vec<CacheEntry>,
+}
+
+/// The binaries removed from the cache
+#[derive(Serialize)]
+struct CacheRemoveReport {
+    cache_dir: PathBuf,
+    removed: Vec<CacheEntry>,
+}
+
+impl CacheCommand {
+    pub fn handle(&self, output: &Output) -> Result<()> {
+        let cache = Cache::new(match &self.cache_dir {
+            Some(dir) => dir.to_owned(),
+            None => Cache::default_dir()?,
+        });
+
+        match self.command {
+            CacheSubcommand::List => {
+                let entries = cache.entries()?;
+
+                match entries.is_empty() {
+                    true => output.print(format_args!(
+                        "The cache at {} is empty",
+                        cache.root().display()
+                    )),
+                    false => {
+                        output.print(format_args!(
+                            "Cached binaries in {}:",
+                            cache.root().display()
+                        ));
+                        print_entries(output, &entries);
+                    }
+                }
+
+                output.report(&CacheListReport {
+                    cache_dir: cache.root().to_path_buf(),
+                    entries,
+                })
+            }
+            CacheSubcommand::Clean => {
+                let removed = cache.clean()?;
+
+                self.print_removed(output, &removed);
+
+                output.report(&CacheRemoveReport {
+                    cache_dir: cache.root().to_path_buf(),
+                    removed,
+                })
+            }
+            CacheSubcommand::Prune { keep } => {
+                let removed = cache.prune(keep)?;
+
+                self.print_removed(output, &removed);
+
+                output.report(&CacheRemoveReport {
+                    cache_dir: cache.root().to_path_buf(),
+                    removed,
+                })
+            }
+        }
+    }
+
+    fn print_removed(&self, output: &Output, removed: &[CacheEntry]) {
+        match removed.len() {
+            0 => output.print("✔ Nothing to remove from the cache".green()),
+            count => {
+                output.print(format!("✔ Removed {count} cached binaries:").green());
+                print_entries(output, removed);
+            }
+        }
+    }
+}
+
+/// Prints one line per cached binary
+fn print_entries(output: &Output, entries: &[CacheEntry]) {
+    for entry in entries {
+        output.print(format_args!(
+            "  {:<10} {:<24} {:.1} MB",
+            entry.version.to_string().bold(),
+            entry.asset,
+            entry.size as f64 / 1_000_000.0
+        ));
+    }
+}
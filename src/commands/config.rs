@@ -0,0 +1,212 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{ArgAction, Args, Subcommand, ValueEnum};
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::{
+    config::{Config, ConfigKey, edit},
+    output::Output,
+};
+
+#[derive(Args, Clone, Debug)]
+pub struct ConfigCommand {
+    #[command(subcommand)]
+    command: ConfigSubcommand,
+
+    /// Manage the system-wide configuration file
+    #[arg(
+        long,
+        action = ArgAction::SetTrue,
+        global = true,
+        help = "Manage the system-wide configuration file instead of the user one",
+        help_heading = "Flags",
+    )]
+    system: bool,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum ConfigSubcommand {
+    /// List the configured values and the files they come from
+    List,
+
+    /// Print the configured value of a key
+    Get {
+        #[arg(value_name = "KEY", help = "The key to print")]
+        key: ConfigKey,
+    },
+
+    /// Set the value of a key
+    Set {
+        #[arg(value_name = "KEY", help = "The key to set")]
+        key: ConfigKey,
+
+        #[arg(value_name = "VALUE", help = "The value of the key")]
+        value: String,
+    },
+
+    /// Remove a key
+    Unset {
+        #[arg(value_name = "KEY", help = "The key to remove")]
+        key: ConfigKey,
+    },
+}
+
+/// The configured values, and the files they come from
+#[derive(Serialize)]
+struct ConfigListReport {
+    user_file: Option<PathBuf>,
+    system_file: PathBuf,
+    values: Vec<ConfigValue>,
+}
+
+/// A configured value
+#[derive(Serialize)]
+struct ConfigValue {
+    key: &'static str,
+    value: serde_json::Value,
+    file: PathBuf,
+}
+
+/// The value of a key
+#[derive(Serialize)]
+struct ConfigGetReport {
+    key: &'static str,
+    value: serde_json::Value,
+}
+
+/// The file that was edited
+#[derive(Serialize)]
+struct ConfigEditReport {
+    file: PathBuf,
+    key: &'static str,
+    value: serde_json::Value,
+}
+
+impl ConfigCommand {
+    pub fn handle(&self, output: &Output) -> Result<()> {
+        match &self.command {
+            ConfigSubcommand::List => self.list(output),
+            ConfigSubcommand::Get { key } => {
+                let value = Config::load()?.get(*key);
+
+                match &value {
+                    serde_json::Value::Null => {
+                        output.print(format_args!("{} is not set", key.name()))
+                    }
+                    serde_json::Value::String(value) => output.print(value),
+                    value => output.print(value),
+                }
+
+                output.report(&ConfigGetReport {
+                    key: key.name(),
+                    value,
+                })
+            }
+            ConfigSubcommand::Set { key, value } => {
+                let file = self.file()?;
+
+                edit(&file, *key, Some(value))?;
+
+                output.print(
+                    format!(
+                        "✔ Set {} in {}",
+                        key.name(),
+                        file.display().to_string().bold()
+                    )
+                    .green(),
+                );
+
+                output.report(&ConfigEditReport {
+                    value: Config::read(&file)?.unwrap_or_default().get(*key),
+                    key: key.name(),
+                    file,
+                })
+            }
+            ConfigSubcommand::Unset { key } => {
+                let file = self.file()?;
+
+                edit(&file, *key, None)?;
+
+                output.print(
+                    format!(
+                        "✔ Removed {} from {}",
+                        key.name(),
+                        file.display().to_string().bold()
+                    )
+                    .green(),
+                );
+
+                output.report(&ConfigEditReport {
+                    file,
+                    key: key.name(),
+                    value: serde_json::Value::Null,
+                })
+            }
+        }
+    }
+
+    /// Lists the configured values, along with the file they come from
+    ///
+    /// Files are listed from the highest precedence to the lowest.
+    fn list(&self, output: &Output) -> Result<()> {
+        let system_file = Config::system_file();
+        let user_file = Config::user_file().ok();
+
+        let mut layers = Vec::new();
+
+        if let Some(file) = &user_file {
+            layers.push((file.clone(), Config::read(file)?));
+        }
+
+        layers.push((system_file.clone(), Config::read(&system_file)?));
+
+        let values: Vec<ConfigValue> = ConfigKey::value_variants()
+            .iter()
+            .filter_map(|key| {
+                layers.iter().find_map(|(file, config)| {
+                    let value = config.as_ref()?.get(*key);
+
+                    (!value.is_null()).then(|| ConfigValue {
+                        key: key.name(),
+                        value,
+                        file: file.clone(),
+                    })
+                })
+            })
+            .collect();
+
+        output.print("Configuration files:");
+
+        for (file, config) in &layers {
+            match config {
+                Some(_) => output.print(format_args!("  {}", file.display())),
+                None => output.print(format!("  {} (missing)", file.display()).dimmed()),
+            }
+        }
+
+        for value in &values {
+            output.print(format_args!(
+                "{} = {} {}",
+                value.key.bold(),
+                value.value,
+                format!("({})", value.file.display()).dimmed()
+            ));
+        }
+
+        output.report(&ConfigListReport {
+            user_file,
+            system_file,
+            values,
+        })
+    }
+
+    /// Returns the configuration file to edit
+    fn file(&self) -> Result<PathBuf> {
+        match self.system {
+            true => Ok(Config::system_file()),
+            false => Config::user_file(),
+        }
+    }
+}
@@ -1,15 +1,37 @@
-use std::path::PathBuf;
+use std::{
+    env::consts::OS,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
 
-use anyhow::{Context, Result};
-use clap::{ArgAction, Args, ValueHint, arg, value_parser};
+use anyhow::{Context, Result, anyhow, bail};
+use clap::{ArgAction, Args, ValueHint, value_parser};
 use colored::Colorize;
-use home::home_dir;
-use inquire::{Confirm, Select};
+use inquire::{Confirm, InquireError, Select};
 use pathman::UpdateType;
+use reqwest::StatusCode;
 use semver::Version;
-use spinners::{Spinner, Spinners};
+use serde::Serialize;
 
-use crate::{downloader::Downloader, installer::Installer, platform::Platform};
+use crate::{
+    bundle::Bundle,
+    cache::Cache,
+    config::{Channel, Config, env_flag},
+    downloader::{Download, DownloadSource, Downloader},
+    error::{InstallerError, prompt_error},
+    http::{Http, HttpOptions},
+    installer::{
+        DEFAULT_LOCK_TIMEOUT, Installer, PathChange, SYSTEM_INSTALL_DIR, check_writable,
+        default_install_dir, system_profile,
+    },
+    npm::NpmRegistry,
+    output::Output,
+    platform::{Libc, Platform, Shell},
+    project::{DEFAULT_LOCAL_DIR, Project},
+    versions::{
+        VersionList, VersionProviderKind, closest_version, fetch_with_fallback, parse_version_list,
+    },
+};
 
 #[derive(Args, Clone, Debug)]
 pub struct InstallCommand {
@@ -29,25 +51,108 @@ pub struct InstallCommand {
         short,
         long,
         value_name = "DIR",
-        env = "BIOME_INSTALL_DIR",
         value_hint = ValueHint::DirPath,
         value_parser = value_parser!(PathBuf),
-        help = "The directory in which to install Biome",
+        help = "The directory in which to install Biome, also read from BIOME_INSTALL_DIR",
     )]
     install_dir: Option<PathBuf>,
 
-    /// Do prepend the installation directory to the PATH environment variable
+    /// The installation directory read from `BIOME_INSTALL_DIR` or from the
+    /// configuration files
+    ///
+    /// Unlike `--install-dir`, it does not conflict with `--local`, which
+    /// takes precedence over it, and is ignored with `--system`.
+    #[arg(skip)]
+    default_install_dir: Option<PathBuf>,
+
+    /// Install Biome into the current project
+    #[arg(
+        long,
+        env = "BIOME_LOCAL",
+        action = ArgAction::SetTrue,
+        conflicts_with = "install_dir",
+        help = "Install Biome into the current project, without updating the PATH",
+        help_heading = "Flags",
+    )]
+    local: bool,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        env = "BIOME_LOCAL_DIR",
+        value_hint = ValueHint::DirPath,
+        value_parser = value_parser!(PathBuf),
+        help = "The directory, relative to the project root, in which to install Biome with `--local` [default: .biome/bin]",
+    )]
+    local_dir: Option<PathBuf>,
+
+    /// Install Biome for every user of the system
+    #[arg(
+        long,
+        env = "BIOME_SYSTEM",
+        action = ArgAction::SetTrue,
+        conflicts_with = "local",
+        help = "Install Biome for every user, into /usr/local/bin unless --install-dir is specified",
+        help_heading = "Flags",
+    )]
+    system: bool,
+
+    /// Link the binary from /usr/local/bin with `--system`
+    #[arg(
+        long,
+        env = "BIOME_SYMLINK",
+        action = ArgAction::SetTrue,
+        requires = "system",
+        help = "Link Biome from /usr/local/bin instead of adding the installation directory to the PATH",
+        help_heading = "Flags",
+    )]
+    symlink: bool,
+
+    /// Prepend the installation directory to the PATH environment variable,
+    /// even if the configuration disables it
+    #[arg(
+        long,
+        action = ArgAction::SetTrue,
+        overrides_with = "no_prepend_path",
+        help = "Prepend the installation directory to the PATH environment variable, also read from BIOME_PREPEND_PATH [default: true]",
+        help_heading = "Flags",
+    )]
+    prepend_path: bool,
+
+    /// Do not prepend the installation directory to the PATH environment
+    /// variable, once merged with the environment and the configuration
     #[arg(
         short,
         long,
-        env = "BIOME_PREPEND_PATH",
         action = ArgAction::SetTrue,
+        overrides_with = "prepend_path",
         help = "Do not prepend the installation directory to the PATH environment variable",
         help_heading = "Flags",
         group = "flags",
     )]
     no_prepend_path: bool,
 
+    #[arg(
+        long,
+        value_name = "SHELLS",
+        env = "BIOME_SHELLS",
+        value_enum,
+        value_delimiter = ',',
+        conflicts_with_all = ["no_prepend_path", "local", "system"],
+        help = "The shells whose configuration file to add the installation directory to, e.g. bash,zsh,fish [default: the current shell]",
+    )]
+    shells: Option<Vec<Shell>>,
+
+    #[arg(
+        long,
+        value_name = "SHELL",
+        env = "BIOME_SHELL",
+        value_enum,
+        conflicts_with = "shells",
+        help = "The shell to add the installation directory to the PATH of, instead of the detected one, without prompting"
+    )]
+    shell: Option<Shell>,
+
     /// Run the installer in non-interactive mode
     #[arg(
         short = 'N',
@@ -59,291 +164,1296 @@ pub struct InstallCommand {
         group = "flags",
     )]
     non_interactive: bool,
+
+    /// Override the detected libc implementation on Linux
+    #[arg(
+        long,
+        value_name = "LIBC",
+        env = "BIOME_LIBC",
+        value_enum,
+        help = "The libc implementation whose build of Biome to install on Linux"
+    )]
+    libc: Option<Libc>,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        env = "BIOME_CACHE_DIR",
+        value_hint = ValueHint::DirPath,
+        value_parser = value_parser!(PathBuf),
+        help = "The directory in which downloaded binaries are cached",
+    )]
+    cache_dir: Option<PathBuf>,
+
+    /// Always download Biome, without reading or writing the cache
+    #[arg(
+        long,
+        env = "BIOME_NO_CACHE",
+        action = ArgAction::SetTrue,
+        help = "Always download Biome, without reading or writing the cache",
+        help_heading = "Flags",
+    )]
+    no_cache: bool,
+
+    /// Only use cached versions and binaries, without accessing the network
+    #[arg(
+        long,
+        env = "BIOME_OFFLINE",
+        action = ArgAction::SetTrue,
+        conflicts_with = "no_cache",
+        help = "Only use cached versions and binaries, without accessing the network",
+        help_heading = "Flags",
+    )]
+    offline: bool,
+
+    /// How long to wait for another installation to finish
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        env = "BIOME_LOCK_TIMEOUT",
+        default_value_t = DEFAULT_LOCK_TIMEOUT,
+        help = "How long to wait for another installation into the same directory to finish",
+    )]
+    lock_timeout: u64,
+
+    /// Print the installation plan without installing anything
+    #[arg(
+        long,
+        env = "BIOME_DRY_RUN",
+        action = ArgAction::SetTrue,
+        help = "Print the installation plan without downloading or installing anything",
+        help_heading = "Flags",
+    )]
+    dry_run: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        env = "BIOME_BUNDLE",
+        value_hint = ValueHint::FilePath,
+        value_parser = value_parser!(PathBuf),
+        conflicts_with_all = ["offline", "no_cache"],
+        help = "Install Biome from a bundle created with `biome-installer bundle`",
+    )]
+    bundle: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "CHANNEL",
+        env = "BIOME_CHANNEL",
+        value_enum,
+        help = "The release channel from which to pick the latest version [default: stable]"
+    )]
+    channel: Option<Channel>,
+
+    #[arg(
+        long,
+        value_name = "SOURCE",
+        env = "BIOME_SOURCE",
+        value_enum,
+        help = "Where to download Biome from [default: github]"
+    )]
+    source: Option<DownloadSource>,
+
+    #[arg(
+        long,
+        value_name = "URL",
+        env = "BIOME_DOWNLOAD_MIRROR",
+        value_hint = ValueHint::Url,
+        help = "The base URL from which to download binaries, instead of GitHub releases",
+    )]
+    download_mirror: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "URL",
+        env = "BIOME_VERSIONS_MIRROR",
+        value_hint = ValueHint::Url,
+        help = "The base URL from which to fetch the lists of versions",
+    )]
+    versions_mirror: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "URL",
+        env = "BIOME_NPM_REGISTRY",
+        value_hint = ValueHint::Url,
+        help = "The npm registry from which to download Biome with `--source npm` [default: https://registry.npmjs.org]",
+    )]
+    npm_registry: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PROVIDER",
+        env = "BIOME_VERSION_PROVIDER",
+        value_enum,
+        help = "Where to look up the published versions of Biome [default: npm with `--source npm`, otherwise auto]"
+    )]
+    version_provider: Option<VersionProviderKind>,
+
+    #[command(flatten)]
+    http: HttpOptions,
+}
+
+/// What an installation would do, as reported by `--dry-run`
+#[derive(Serialize)]
+struct InstallPlan<'a> {
+    dry_run: bool,
+    version: Version,
+    version_source: VersionSource,
+    platform: &'a Platform,
+    git_tag: String,
+    asset: String,
+    asset_url: String,
+    destination: PathBuf,
+    project: Option<PathBuf>,
+    /// The first of `path_updates`, kept for scripts written before several
+    /// shells could be updated
+    path_update: Option<PathChange>,
+    path_updates: Vec<PathChange>,
+}
+
+/// The outcome of an installation
+#[derive(Serialize)]
+struct InstallReport {
+    version: Version,
+    version_source: VersionSource,
+    asset_url: String,
+    cached: bool,
+    destination: PathBuf,
+    project: Option<PathBuf>,
+    link: Option<PathBuf>,
+    backup: Option<PathBuf>,
+    path_update: PathUpdate,
+    path_updates: Vec<ShellPathUpdate>,
+}
+
+/// Where the installed version of Biome comes from
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum VersionSource {
+    /// The version was specified with `--version` or `BIOME_VERSION`
+    Argument,
+
+    /// The version was chosen by the user from the list of versions
+    Prompt,
+
+    /// The requested version was not published, and the user accepted the
+    /// closest one instead
+    Closest,
+
+    /// The version is the latest one published
+    Latest,
+
+    /// The version is the most recent one in the bundle
+    Bundle,
+
+    /// The version is the one recorded for the project with `--local`
+    Project,
+}
+
+impl Display for VersionSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VersionSource::Argument => write!(f, "argument"),
+            VersionSource::Prompt => write!(f, "prompt"),
+            VersionSource::Closest => write!(f, "closest"),
+            VersionSource::Latest => write!(f, "latest"),
+            VersionSource::Bundle => write!(f, "bundle"),
+            VersionSource::Project => write!(f, "project"),
+        }
+    }
+}
+
+/// The outcome of the PATH update
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum PathUpdate {
+    /// The installation directory was added to the PATH
+    Success,
+
+    /// The installation directory was already in the PATH
+    AlreadyInPath,
+
+    /// The PATH update was disabled with `--no-prepend-path`
+    Skipped,
+
+    /// The user declined to update their PATH
+    Declined,
+
+    /// Biome was installed into a project with `--local`, which does not
+    /// update the PATH
+    Local,
+
+    /// The binary was linked from a directory that is already in the PATH
+    /// with `--symlink`
+    Linked,
+}
+
+/// The outcome of the PATH update of one shell
+#[derive(Serialize)]
+struct ShellPathUpdate {
+    /// The shell whose configuration file was updated, if any
+    shell: Option<Shell>,
+    outcome: PathUpdate,
+}
+
+impl From<UpdateType> for PathUpdate {
+    fn from(update_type: UpdateType) -> Self {
+        match update_type {
+            UpdateType::Success => PathUpdate::Success,
+            UpdateType::AlreadyInPath => PathUpdate::AlreadyInPath,
+        }
+    }
 }
 
 impl InstallCommand {
-    pub fn handle(&self) -> Result<()> {
-        let version = match self.should_prompt() {
-            true => self.prompt_version()?,
-            false => self.version.clone().unwrap_or(self.get_latest_version()?),
+    /// Fills the values that were not specified with those of the
+    /// configuration files
+    ///
+    /// Flags take precedence over environment variables, which take
+    /// precedence over the user configuration file, which takes precedence
+    /// over the system-wide one.
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.http = self.http.with_config(&config);
+        self.default_install_dir = std::env::var_os("BIOME_INSTALL_DIR")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or(config.install_dir);
+        self.local_dir = self.local_dir.or(config.local_dir);
+        self.channel = self.channel.or(config.channel);
+        self.source = self.source.or(config.source);
+        self.download_mirror = self.download_mirror.or(config.download_mirror);
+        self.npm_registry = self.npm_registry.or(config.npm_registry);
+        self.versions_mirror = self.versions_mirror.or(config.versions_mirror);
+        self.version_provider = self.version_provider.or(config.version_provider);
+        // Selecting shells explicitly overrides the configuration, since
+        // their PATH could not be updated otherwise
+        self.no_prepend_path = match (self.prepend_path, self.no_prepend_path) {
+            (true, _) => false,
+            (_, true) => true,
+            _ => match env_flag("BIOME_PREPEND_PATH") {
+                Some(prepend_path) => !prepend_path,
+                None => {
+                    self.shells.is_none()
+                        && self.shell.is_none()
+                        && config.prepend_path == Some(false)
+                }
+            },
         };
+        self
+    }
 
-        let temp_file = self
-            .download(version.clone())
-            .context("Failed to download the specified version of Biome")?;
+    pub fn handle(&self, output: &Output) -> Result<()> {
+        if self.system && OS == "windows" {
+            bail!("Installing Biome for every user with --system is not supported on Windows");
+        }
 
-        let install_dir = match &self.install_dir {
-            Some(dir) => dir.to_owned(),
-            None => home_dir()
-                .context("Could not determine the home directory")?
-                .join(".biome")
-                .join("bin"),
+        if (self.shell.is_some() || self.shells.is_some()) && OS == "windows" {
+            bail!("Selecting shells with --shell or --shells is not supported on Windows");
+        }
+
+        if let Some(source) = self.http.insecure_source() {
+            output.warn(format!(
+                "TLS certificates will not be verified, as requested {source}."
+            ));
+        }
+
+        let http = self.http.client()?;
+
+        let mut downloader = self.downloader(self.libc, &http)?;
+
+        let project = match self.local {
+            true => Some(Project::find(
+                &std::env::current_dir().context("Could not determine the current directory")?,
+            )?),
+            false => None,
+        };
+
+        let recorded = match &project {
+            Some(project) => project.version()?,
+            None => None,
+        };
+
+        let (mut version, mut version_source) = match (&self.version, recorded, downloader.bundle())
+        {
+            (Some(version), _, _) => (version.clone(), VersionSource::Argument),
+            (None, Some(recorded), _) => (recorded, VersionSource::Project),
+            (None, None, Some(bundle)) => (
+                self.get_bundled_version(&downloader, bundle)?,
+                VersionSource::Bundle,
+            ),
+            (None, None, None) => match self.should_prompt(output) {
+                true => (self.prompt_version(output, &http)?, VersionSource::Prompt),
+                false => (
+                    self.get_latest_version(output, &http)?,
+                    VersionSource::Latest,
+                ),
+            },
         };
 
+        let install_dir = self.install_dir(project.as_ref())?;
+
         let installer = Installer::new(install_dir.clone());
 
-        let destination = installer
-            .install(temp_file)
-            .context("Failed to install Biome")?;
+        if self.dry_run {
+            return self.print_plan(
+                output,
+                &downloader,
+                &installer,
+                project.as_ref(),
+                version,
+                version_source,
+            );
+        }
+
+        if self.system {
+            self.check_system_permissions(&installer)?;
+        }
+
+        installer.prepare()?;
+
+        // Hold the lock from the download until the PATH has been updated
+        let _lock = super::lock(output, &installer, self.lock_timeout)?;
+
+        if downloader.bundle().is_none()
+            && !self.offline
+            && let Some(closest) = self.check_availability(output, &http, &downloader, &version)?
+        {
+            (version, version_source) = (closest, VersionSource::Closest);
+        }
+
+        let (destination, cached) =
+            match self.download_and_install(output, &downloader, &installer, &version) {
+                Ok(installed) => installed,
+                Err(err) => {
+                    let Some(InstallerError::BrokenBinary {
+                        unloadable: true, ..
+                    }) = InstallerError::find(&err)
+                    else {
+                        return Err(err);
+                    };
+
+                    downloader = self.recover_broken_binary(output, &downloader, &http, err)?;
+
+                    self.download_and_install(output, &downloader, &installer, &version)?
+                }
+            };
 
-        println!(
-            "{}",
+        let asset_url = downloader.get_download_url(&version)?;
+
+        output.print(
             format!(
                 "✔ Biome has been installed to {}",
                 format!("{}", destination.display()).bold()
             )
-            .green()
+            .green(),
         );
 
-        self.prepend_install_dir_to_path_if_needed(&installer, install_dir)?;
+        let backup = Some(installer.backup()).filter(|backup| backup.exists());
 
-        println!(
-            "\n{}\n{}\n{}",
-            format!(
-                "❤️ Thank you for installing {}!",
-                format!("Biome").blue().bold()
+        if backup.is_some() {
+            output.print(format_args!(
+                "The previous version has been kept, run {} to restore it.",
+                "biome-installer rollback".bold()
+            ));
+        }
+
+        let link = match self.symlink {
+            true => Some(self.link(output, &installer)?),
+            false => None,
+        };
+
+        let (path_update, path_updates) = match (&project, &link) {
+            (Some(project), _) => {
+                project.record_version(&version)?;
+
+                self.print_local_usage(output, project, &destination, &version);
+
+                (PathUpdate::Local, vec![])
+            }
+            (None, Some(_)) => (PathUpdate::Linked, vec![]),
+            (None, None) if self.system => (
+                self.prepend_install_dir_to_system_path(output, &installer)?,
+                vec![],
             ),
-            format!(
+            (None, None) => self.prepend_install_dir_to_path_if_needed(
+                output,
+                &installer,
+                install_dir,
+                &downloader.platform,
+            )?,
+        };
+
+        output.print(format_args!(
+            "\n{}\n{}\n{}",
+            format_args!("❤️ Thank you for installing {}!", "Biome".blue().bold()),
+            format_args!(
                 "⭐ Support the project — star us on GitHub! {}",
-                format!("https://github.com/biomejs/biome").underline()
+                "https://github.com/biomejs/biome".underline()
             ),
-            format!(
+            format_args!(
                 "📖 Learn more about Biome at {}",
-                format!("https://biomejs.dev").underline()
+                "https://biomejs.dev".underline()
             )
-        );
+        ));
+
+        output.report(&InstallReport {
+            version,
+            version_source,
+            asset_url,
+            cached,
+            destination,
+            project: project.map(|project| project.root().to_path_buf()),
+            link,
+            backup,
+            path_update,
+            path_updates,
+        })
+    }
+
+    /// Creates the downloader, for the specified libc implementation if any
+    fn downloader(&self, libc: Option<Libc>, http: &Http) -> Result<Downloader> {
+        let mut downloader = Downloader::new(http.clone());
+
+        if let Some(libc) = libc {
+            downloader = downloader.with_libc(libc);
+        }
+
+        if let Some(mirror) = &self.download_mirror {
+            downloader = downloader.with_mirror(mirror);
+        }
+
+        if self.source == Some(DownloadSource::Npm) {
+            downloader = downloader
+                .with_npm_registry(NpmRegistry::new(http.clone(), self.npm_registry.as_deref()));
+        }
+
+        if let Some(bundle) = &self.bundle {
+            return Ok(downloader.with_bundle(Bundle::open(bundle)?));
+        }
+
+        if let Some(cache) = self.cache()? {
+            downloader = downloader.with_cache(cache);
+        }
+
+        if self.offline {
+            downloader = downloader.offline();
+        }
+
+        Ok(downloader)
+    }
+
+    /// Opens the cache, unless disabled with `--no-cache`
+    fn cache(&self) -> Result<Option<Cache>> {
+        if self.no_cache {
+            return Ok(None);
+        }
+
+        let cache_dir = match &self.cache_dir {
+            Some(dir) => dir.to_owned(),
+            None => Cache::default_dir()?,
+        };
+
+        Ok(Some(Cache::new(cache_dir)))
+    }
+
+    /// Downloads the specified version of Biome and installs it
+    ///
+    /// It returns the path to the installed binary, and whether it was
+    /// reused from the cache.
+    fn download_and_install(
+        &self,
+        output: &Output,
+        downloader: &Downloader,
+        installer: &Installer,
+        version: &Version,
+    ) -> Result<(PathBuf, bool)> {
+        let download = self
+            .download(output, downloader, installer, version.clone())
+            .context("Failed to download the specified version of Biome")?;
+
+        let destination = installer
+            .install(download.path, version)
+            .context("Failed to install Biome")?;
+
+        Ok((destination, download.cached))
+    }
+
+    /// Checks that the specified version has a build for the current platform
+    ///
+    /// Otherwise, the closest version that has one is looked up, and offered
+    /// to the user in interactive mode. It returns that version if the user
+    /// accepts it.
+    fn check_availability(
+        &self,
+        output: &Output,
+        http: &Http,
+        downloader: &Downloader,
+        version: &Version,
+    ) -> Result<Option<Version>> {
+        let asset = downloader.get_asset_name()?;
+
+        if downloader.has_asset(version, &asset)? {
+            return Ok(None);
+        }
+
+        // A version missing from the list does not exist at all, rather than
+        // lacking a build for this platform. Without a list to tell, the
+        // download reports what is missing instead
+        let versions = match self.fetch_versions(output, http, VersionList::Preview, Some(version))
+        {
+            Ok(versions) if versions.contains(version) => versions,
+            Ok(_) => return Err(InstallerError::VersionNotFound(version.clone()).into()),
+            Err(err) if is_missing_list(&err) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let spinner = output.spinner("Looking for the closest version with a build...");
+
+        let closest = closest_version(&versions, version, |candidate| {
+            downloader.has_asset(candidate, &asset)
+        })?;
+
+        spinner.success("Looking for the closest version with a build");
+
+        let err = InstallerError::AssetNotFound {
+            version: version.clone(),
+            platform: downloader.platform.to_string(),
+            closest: closest.clone(),
+        };
+
+        let Some(closest) = closest.filter(|_| self.should_prompt(output)) else {
+            return Err(err.into());
+        };
+
+        output.warn(err.to_string());
+
+        match Confirm::new(&format!("Do you want to install Biome {closest} instead?"))
+            .with_default(true)
+            .prompt()
+            .map_err(prompt_error)?
+        {
+            true => Ok(Some(closest)),
+            false => Err(err.into()),
+        }
+    }
+
+    /// Explains why the installed binary could not be loaded
+    ///
+    /// On Linux, the most likely cause is that the binary was built against
+    /// another libc implementation than the one used by the system, which we
+    /// detect from the output of `ldd --version`. In that case, this function
+    /// offers to retry with the other variant, and returns a downloader for
+    /// it if the user accepts.
+    fn recover_broken_binary(
+        &self,
+        output: &Output,
+        downloader: &Downloader,
+        http: &Http,
+        err: anyhow::Error,
+    ) -> Result<Downloader> {
+        let platform = &downloader.platform;
+
+        if platform.os != "linux" {
+            output.warn(format!(
+                "The binary may have been built for another architecture than {}.",
+                platform.arch
+            ));
+
+            return Err(err);
+        }
+
+        let libc = platform.libc;
+        let other = libc.other();
+
+        match self.libc {
+            Some(_) => output.warn(format!(
+                "The {libc} build of Biome, requested with --libc, does not run on this system."
+            )),
+            None => output.warn(format!(
+                "The {libc} build of Biome does not run on this system, although `ldd --version` \
+                 suggests that it uses {libc}. It may be using {other} instead."
+            )),
+        }
+
+        let should_retry = match self.should_prompt(output) {
+            true => Confirm::new(&format!("Do you want to try the {other} build instead?"))
+                .with_default(true)
+                .prompt()
+                .map_err(prompt_error)?,
+            false => false,
+        };
+
+        match should_retry {
+            true => self.downloader(Some(other), http),
+            false => Err(err.context(format!(
+                "Failed to install the {libc} build of Biome, \
+                 re-run with `--libc {other}` to try the {other} build"
+            ))),
+        }
+    }
+
+    /// Returns the directory in which Biome should be installed
+    ///
+    /// This is the directory specified with `--install-dir`, or in
+    /// `BIOME_INSTALL_DIR` or the configuration files, or `~/.biome/bin`.
+    /// With `--system`, this is `/usr/local/bin` unless `--install-dir` is
+    /// specified, since the other defaults are meant for the current user.
+    /// With `--local`, this is the local directory, by default `.biome/bin`,
+    /// relative to the root of the project.
+    fn install_dir(&self, project: Option<&Project>) -> Result<PathBuf> {
+        match (project, &self.install_dir, &self.default_install_dir) {
+            (Some(project), _, _) => Ok(project.root().join(
+                self.local_dir
+                    .as_deref()
+                    .unwrap_or(Path::new(DEFAULT_LOCAL_DIR)),
+            )),
+            (None, Some(dir), _) => Ok(dir.to_owned()),
+            (None, None, _) if self.system => Ok(PathBuf::from(SYSTEM_INSTALL_DIR)),
+            (None, None, Some(dir)) => Ok(dir.to_owned()),
+            (None, None, None) => default_install_dir(),
+        }
+    }
+
+    /// Checks whether a system-wide installation writes the system profile
+    ///
+    /// This is not needed when Biome is installed into, or linked from,
+    /// `/usr/local/bin`, which is already in the PATH.
+    fn writes_system_profile(&self, installer: &Installer) -> bool {
+        self.system
+            && !self.symlink
+            && !self.no_prepend_path
+            && installer.install_dir() != Path::new(SYSTEM_INSTALL_DIR)
+    }
+
+    /// Checks that a system-wide installation is allowed to write everywhere
+    /// it needs to, before anything is downloaded
+    ///
+    /// Otherwise, it explains how to re-run the installer with elevated rights.
+    fn check_system_permissions(&self, installer: &Installer) -> Result<()> {
+        let mut dirs = vec![installer.install_dir().to_path_buf()];
+
+        if self.symlink {
+            dirs.push(PathBuf::from(SYSTEM_INSTALL_DIR));
+        }
+
+        if self.writes_system_profile(installer)
+            && let Some(dir) = system_profile().parent()
+        {
+            dirs.push(dir.to_path_buf());
+        }
+
+        for dir in dirs {
+            check_writable(&dir).with_context(|| {
+                format!(
+                    "Installing Biome for every user requires write access to {}, \
+                     re-run the installer with elevated rights: {}",
+                    dir.display(),
+                    elevated_command()
+                )
+            })?;
+        }
 
         Ok(())
     }
 
+    /// Links the installed binary from `/usr/local/bin`
+    fn link(&self, output: &Output, installer: &Installer) -> Result<PathBuf> {
+        let link = installer.link(Path::new(SYSTEM_INSTALL_DIR))?;
+
+        output.print(
+            format!(
+                "✔ Biome has been linked from {}",
+                format!("{}", link.display()).bold()
+            )
+            .green(),
+        );
+
+        Ok(link)
+    }
+
+    /// Adds the installation directory to the PATH of every user, unless it
+    /// is already in it
+    fn prepend_install_dir_to_system_path(
+        &self,
+        output: &Output,
+        installer: &Installer,
+    ) -> Result<PathUpdate> {
+        let install_dir = installer.install_dir();
+
+        if install_dir == Path::new(SYSTEM_INSTALL_DIR) {
+            output.print(
+                format!(
+                    "The installation directory {} is already in the PATH of every user",
+                    install_dir.display()
+                )
+                .yellow(),
+            );
+            return Ok(PathUpdate::AlreadyInPath);
+        }
+
+        if self.no_prepend_path {
+            output.print(
+                "As requested, the installation directory will not be added to the PATH.".yellow(),
+            );
+            return Ok(PathUpdate::Skipped);
+        }
+
+        let update_type = installer.prepend_install_dir_to_system_path()?;
+
+        match update_type {
+            UpdateType::Success => {
+                output.print(
+                    format!(
+                        "✔ The installation directory {} has been added to the PATH of every user in {}",
+                        install_dir.display(),
+                        system_profile().display()
+                    )
+                    .green(),
+                );
+
+                output.print("Users need to log in again to apply the changes.".green());
+            }
+            UpdateType::AlreadyInPath => {
+                output.print(
+                    format!(
+                        "The installation directory {} is already in {}",
+                        install_dir.display(),
+                        system_profile().display()
+                    )
+                    .yellow(),
+                );
+            }
+        }
+
+        Ok(update_type.into())
+    }
+
+    /// Explains how to run Biome once installed into a project
+    fn print_local_usage(
+        &self,
+        output: &Output,
+        project: &Project,
+        destination: &Path,
+        version: &Version,
+    ) {
+        let command = match destination.strip_prefix(project.root()) {
+            Ok(relative) => Path::new(".").join(relative),
+            Err(_) => destination.to_path_buf(),
+        };
+
+        output.print(format_args!(
+            "Biome {version} has been recorded in {}.",
+            project.version_file().display()
+        ));
+        output.print(format_args!(
+            "Your PATH has not been changed, run {} from {} to use it.",
+            format!("{} check", command.display()).bold(),
+            project.root().display()
+        ));
+    }
+
+    /// Prints what the installation would do
+    ///
+    /// This function only performs read-only lookups: nothing is downloaded,
+    /// and neither the installation directory nor the PATH are modified.
+    fn print_plan(
+        &self,
+        output: &Output,
+        downloader: &Downloader,
+        installer: &Installer,
+        project: Option<&Project>,
+        version: Version,
+        version_source: VersionSource,
+    ) -> Result<()> {
+        let platform = &downloader.platform;
+
+        let path_updates: Vec<PathChange> = match (self.local, self.system) {
+            (true, _) => vec![],
+            (false, true) => self
+                .writes_system_profile(installer)
+                .then(|| installer.plan_system_path_update())
+                .into_iter()
+                .collect(),
+            (false, false) if self.no_prepend_path => vec![],
+            (false, false) => self
+                .selected_shells(platform)
+                .into_iter()
+                .filter_map(|shell| installer.plan_path_update(shell))
+                .collect(),
+        };
+
+        let plan = InstallPlan {
+            dry_run: true,
+            git_tag: downloader.get_git_tag(&version),
+            asset: downloader.get_asset_name()?,
+            asset_url: downloader.get_download_url(&version)?,
+            destination: installer.destination(),
+            project: project.map(|project| project.root().to_path_buf()),
+            path_update: path_updates.first().cloned(),
+            path_updates,
+            version,
+            version_source,
+            platform,
+        };
+
+        output.print("Installation plan (dry run):".bold());
+        output.print(format_args!(
+            "  Version:      {} ({})",
+            plan.version, plan.version_source
+        ));
+        output.print(format_args!(
+            "  Platform:     {} {} {}",
+            platform.os, platform.arch, platform.libc
+        ));
+        output.print(format_args!("  Git tag:      {}", plan.git_tag));
+        output.print(format_args!("  Asset:        {}", plan.asset));
+        output.print(format_args!("  Download URL: {}", plan.asset_url));
+        output.print(format_args!(
+            "  Destination:  {}",
+            plan.destination.display()
+        ));
+
+        if let Some(root) = &plan.project {
+            output.print(format_args!("  Project:      {}", root.display()));
+        }
+
+        match (plan.path_updates.as_slice(), self.no_prepend_path) {
+            (_, _) if self.local => output.print("  PATH update:  none, installing with --local"),
+            (_, _) if self.symlink => output.print(format_args!(
+                "  PATH update:  none, link Biome from {SYSTEM_INSTALL_DIR}"
+            )),
+            ([], false) if self.system => output.print(format_args!(
+                "  PATH update:  none, {SYSTEM_INSTALL_DIR} is already in the PATH"
+            )),
+            (_, true) => output.print("  PATH update:  disabled, as requested"),
+            ([], _) => output.print(format_args!(
+                "  PATH update:  none, no shell configuration file was found"
+            )),
+            (changes, _) => {
+                for change in changes {
+                    match change.already_present {
+                        true => output.print(format_args!(
+                            "  PATH update:  none, {} already contains `{}`",
+                            change.target, change.line
+                        )),
+                        false => output.print(format_args!(
+                            "  PATH update:  add `{}` to {}",
+                            change.line, change.target
+                        )),
+                    }
+                }
+            }
+        }
+
+        for change in &plan.path_updates {
+            warn_if_skipped_by_login_shells(output, change);
+        }
+
+        output.report(&plan)
+    }
+
     /// Prompts the user to choose a version of Biome to install
     ///
     /// This function will display a list of available versions and allow the
     /// user to select one.
-    fn prompt_version(&self) -> Result<Version> {
+    fn prompt_version(&self, output: &Output, http: &Http) -> Result<Version> {
         let (latest, versions) = self
-            .get_versions()
+            .get_versions(output, http)
             .context("Could not retrieve the list of versions")?;
 
         match Select::new("Please choose a version:", versions).prompt() {
             Ok(version) => Ok(version),
-            Err(_) => Ok(latest),
+            Err(InquireError::OperationCanceled) => Ok(latest),
+            Err(err) => Err(prompt_error(err)),
         }
     }
 
-    /// Prompts the user to ask them if they want to update their PATH
-    fn prompt_update_path(&self) -> Result<bool> {
+    /// Prompts the user to choose the shells whose PATH to update
+    ///
+    /// Every shell with a configuration file is offered, and selected by
+    /// default. On Windows, where the PATH is stored in the registry, the user
+    /// is only asked whether to update it.
+    fn prompt_update_path(&self) -> Result<Vec<Option<Shell>>> {
         #[cfg(unix)]
         {
-            let platform = Platform::detect();
-
-            let shell_config: Option<PathBuf> = match platform.shell {
-                Some(shell) => match shell.config_file() {
-                    Ok(path) => Some(path),
-                    Err(_) => None,
-                },
-                None => None,
-            };
+            let shells = crate::shell_config::configured_shells()?;
 
-            if let Some(shell_config) = shell_config {
-                return Ok(Confirm::new("Do you want to update your PATH?")
-                    .with_default(true)
-                    .with_help_message(&format!(
-                        r#"We'll add the export to your shell config file at {}"#,
-                        shell_config.display(),
-                    ))
-                    .prompt()?);
+            if shells.is_empty() {
+                return Ok(vec![]);
             }
 
-            return Ok(false);
+            let options = shells
+                .iter()
+                .map(|(shell, file)| format!("{shell} ({})", file.display()))
+                .collect();
+
+            let selected = inquire::MultiSelect::new(
+                "Which shells do you want to add the installation directory to the PATH of?",
+                options,
+            )
+            .with_all_selected_by_default()
+            .with_help_message("We'll add the export to the config file of each selected shell")
+            .raw_prompt()
+            .map_err(prompt_error)?;
+
+            Ok(selected
+                .into_iter()
+                .map(|option| Some(shells[option.index].0))
+                .collect())
         }
 
         #[cfg(windows)]
         {
-            return Ok(Confirm::new("Do you want to update your PATH?")
+            let confirmed = Confirm::new("Do you want to update your PATH?")
                 .with_default(true)
                 .with_help_message(
                     "We will prepend the installation directory to your PATH environment variable",
                 )
-                .prompt()?);
+                .prompt()
+                .map_err(prompt_error)?;
+
+            Ok(match confirmed {
+                true => vec![None],
+                false => vec![],
+            })
         }
     }
 
-    /// Fetches the list of available versions of Biome
+    /// Fetches the latest version of Biome
     ///
-    /// This function retrieves the list of available Biome versions from our
-    /// version API and returns the latest version along with all available
-    /// versions.
-    fn get_latest_version(&self) -> Result<Version> {
-        let mut spinner = Spinner::new(Spinners::Dots, "Fetching latest version...".into());
-
-        let response = reqwest::blocking::get("https://biomejs.dev/api/versions/latest.txt")
-            .context("Failed to fetch the latest version of Biome")?;
-
-        let version = response
-            .text()
-            .context("Failed to read response")?
-            .trim()
-            .to_string()
-            .parse::<Version>()
-            .context("Failed to parse latest version")?;
-
-        spinner.stop_and_persist(
-            &"✔".green().to_string(),
-            format!("Latest version is: {}", format!("{version}").bold())
-                .green()
-                .to_string(),
-        );
+    /// This function retrieves the latest version of Biome on the selected
+    /// channel.
+    fn get_latest_version(&self, output: &Output, http: &Http) -> Result<Version> {
+        let spinner = output.spinner("Fetching latest version...");
+
+        let list = match self.channel.unwrap_or_default() {
+            Channel::Stable => VersionList::Latest,
+            Channel::Preview => VersionList::Preview,
+        };
+
+        let version = self
+            .fetch_versions(output, http, list, None)
+            .context("Failed to fetch the latest version of Biome")?
+            .into_iter()
+            .max()
+            .context("No versions available")?;
+
+        spinner.success(format!(
+            "Latest version is: {}",
+            format!("{version}").bold()
+        ));
 
         Ok(version)
     }
 
     /// Fetches the list of available versions of Biome
     ///
-    /// This function retrieves the list of available Biome versions from our
-    /// version API and returns the latest version along with all available
-    /// versions.
-    fn get_versions(&self) -> Result<(Version, Vec<Version>)> {
-        let mut spinner = Spinner::new(Spinners::Dots, "Fetching the list of versions...".into());
+    /// This function retrieves the list of available Biome versions on the
+    /// selected channel and returns the latest version along with all
+    /// available versions.
+    fn get_versions(&self, output: &Output, http: &Http) -> Result<(Version, Vec<Version>)> {
+        let spinner = output.spinner("Fetching the list of versions...");
 
-        let response = reqwest::blocking::get("https://biomejs.dev/api/versions/stable.txt")
-            .context("Failed to fetch the list of Biome version")?;
+        let list = match self.channel.unwrap_or_default() {
+            Channel::Stable => VersionList::Stable,
+            Channel::Preview => VersionList::Preview,
+        };
 
-        let versions: Vec<Version> = response
-            .text()
-            .context("Failed to read response")?
-            .lines()
-            .map(|line| line.trim().to_string())
-            .filter(|line| !line.is_empty())
-            .filter_map(|line| Version::parse(&line).ok())
-            .collect();
+        let versions = self
+            .fetch_versions(output, http, list, None)
+            .context("Failed to fetch the list of Biome version")?;
 
         let latest = versions.first().context("No versions available")?.clone();
 
-        spinner.stop_and_persist(
-            &"✔".green().to_string(),
-            format!("Fetching the list of versions"),
-        );
+        spinner.success("Fetching the list of versions");
 
         Ok((latest, versions))
     }
 
+    /// Returns the most recent version of Biome in the bundle that can be
+    /// installed on the current platform
+    fn get_bundled_version(&self, downloader: &Downloader, bundle: &Bundle) -> Result<Version> {
+        let asset = downloader.get_asset_name()?;
+
+        let entry = bundle.find(None, &asset).with_context(|| {
+            format!(
+                "The bundle {} does not contain {asset}",
+                bundle.path().display()
+            )
+        })?;
+
+        Ok(entry.version.clone())
+    }
+
+    /// Fetches a list of versions
+    ///
+    /// The list is fetched from the selected version providers, in order,
+    /// until one of them succeeds. With `until`, the list may stop at that
+    /// version. Complete lists fetched from the network are stored in the
+    /// cache, unless this is a dry run, and read back from it in offline mode.
+    fn fetch_versions(
+        &self,
+        output: &Output,
+        http: &Http,
+        list: VersionList,
+        until: Option<&Version>,
+    ) -> Result<Vec<Version>> {
+        let cache = self.cache()?;
+        let name = list.file_name();
+
+        if self.offline {
+            return match cache.map(|cache| cache.version_list(name)).transpose()? {
+                Some(Some(versions)) => parse_version_list(&versions),
+                _ => {
+                    Err(InstallerError::NotInCache(format!("The list of versions ({name})")).into())
+                }
+            };
+        }
+
+        // Versions are looked up where binaries are downloaded from, unless
+        // another source is requested
+        let provider = match (self.version_provider, self.source.unwrap_or_default()) {
+            (Some(provider), _) => provider,
+            (None, DownloadSource::Npm) => VersionProviderKind::Npm,
+            (None, DownloadSource::Github) => VersionProviderKind::Auto,
+        };
+
+        let providers = provider.providers(
+            http,
+            self.versions_mirror.as_deref(),
+            self.npm_registry.as_deref(),
+        );
+
+        let versions = fetch_with_fallback(&providers, list, until, |provider, err| {
+            output.warn(format!(
+                "Could not fetch the list of versions from {}, trying the next source: {err}",
+                provider.name()
+            ))
+        })?;
+
+        // Failing to populate the cache must not fail the installation. A dry
+        // run must not populate it at all, and a list that may stop at
+        // `until` must not be taken for a complete one
+        if let Some(cache) = cache.filter(|_| !self.dry_run && until.is_none()) {
+            let content: String = versions
+                .iter()
+                .map(|version| format!("{version}\n"))
+                .collect();
+            let _ = cache.store_version_list(name, &content);
+        }
+
+        Ok(versions)
+    }
+
     /// Checks if the installer should prompt the user for input
     ///
     /// This function determines whether the installer should prompt the user
     /// for inputs based on the `non_interactive` flag and whether the
     /// terminal supports interactivity, or is running in a CI environment.
+    /// Machine-readable output also implies a non-interactive run.
     ///
     /// We first check the flag set by the user, then check if running in a CI
     /// environment because some CI envs simulate an interactive terminal,
     /// and finally check if the terminal supports interactivity.
-    fn should_prompt(&self) -> bool {
+    fn should_prompt(&self, output: &Output) -> bool {
         let is_interactive = atty::is(atty::Stream::Stdin) && atty::is(atty::Stream::Stdout);
 
         // If the CI environment variable is set, we assume we're running
         // in a CI environment.
         let runs_in_ci = std::env::var_os("CI").is_some();
 
-        return !self.non_interactive && !runs_in_ci && is_interactive;
+        !self.non_interactive && !output.is_json() && !runs_in_ci && is_interactive
     }
 
     /// Downloads the specified version of Biome
     ///
-    /// This function downloads the specified version of Biome and returns the
-    /// path to the downloaded file.
-    fn download(&self, version: Version) -> Result<PathBuf> {
-        let mut spinner = Spinner::new(
-            Spinners::Dots,
-            format!("Downloading Biome {}", &version).into(),
-        );
-
-        let downloader = Downloader::new();
+    /// This function downloads the specified version of Biome into the
+    /// installation directory and returns the path to the downloaded file.
+    fn download(
+        &self,
+        output: &Output,
+        downloader: &Downloader,
+        installer: &Installer,
+        version: Version,
+    ) -> Result<Download> {
+        let spinner = output.spinner(format!("Downloading Biome {}", &version));
 
-        let temp_file = downloader.download(version.clone())?;
+        let download = downloader.download(version.clone(), installer.install_dir())?;
 
-        spinner.stop_and_persist(
-            &"✔".green().to_string(),
-            format!("Downloaded Biome {}", &version).green().to_string(),
-        );
+        match (download.cached, downloader.bundle()) {
+            (true, _) => spinner.success(format!("Using cached Biome {}", &version)),
+            (false, Some(_)) => spinner.success(format!("Extracted Biome {}", &version)),
+            (false, None) => spinner.success(format!("Downloaded Biome {}", &version)),
+        }
 
-        Ok(temp_file)
+        Ok(download)
     }
 
     fn prepend_install_dir_to_path_if_needed(
         &self,
+        output: &Output,
         installer: &Installer,
         install_dir: PathBuf,
-    ) -> Result<()> {
+        platform: &Platform,
+    ) -> Result<(PathUpdate, Vec<ShellPathUpdate>)> {
         // If the user has explicitly told us not to prepend the
         // installation directory to the PATH environment variable, we're done
         if self.no_prepend_path {
-            println!(
-                "{}",
-                format!("As requested, the installation directory will not be added to your PATH.")
-                    .yellow()
+            output.print(
+                "As requested, the installation directory will not be added to your PATH.".yellow(),
             );
-            return Ok(());
+            return Ok((PathUpdate::Skipped, vec![]));
         }
 
-        // Otherwise, ask the user if possible, or just update the PATH in
-        // non-interactive environments
-        let should_update_path = match self.should_prompt() {
-            true => self.prompt_update_path(),
-            false => Ok(true),
+        // Otherwise, update the configuration file of the requested shells, or
+        // ask the user if possible, or just update the PATH of the current
+        // shell in non-interactive environments
+        let requested = self.shells.is_some() || self.shell.is_some();
+
+        let shells = match !requested && self.should_prompt(output) {
+            true => self.prompt_update_path()?,
+            false => self.selected_shells(platform),
         };
 
         // We're done if the PATH update was not requested, but we'll tell the
         // user to update their PATH manually
-        if !should_update_path? {
-            println!(
-                "{}",
-                format!(
-                    "⚠ Please update your PATH manually to include: {}",
-                    install_dir.display()
-                )
-                .yellow()
-            );
+        if shells.is_empty() {
+            output.warn(format!(
+                "Please update your PATH manually to include: {}",
+                install_dir.display()
+            ));
 
-            return Ok(());
+            return Ok((PathUpdate::Declined, vec![]));
         }
 
-        match installer.prepend_install_dir_to_path() {
-            Ok(update_type) => match update_type {
+        let mut path_updates = Vec::new();
+        let mut failures = Vec::new();
+
+        // Update every shell, even if one of them fails, so that a single
+        // broken configuration file does not prevent the others from working
+        for shell in shells {
+            let update_type = match installer.prepend_install_dir_to_path(shell) {
+                Ok(update_type) => update_type,
+                Err(err) => {
+                    failures.push(match shell {
+                        Some(shell) => {
+                            format!("Could not update the PATH of {shell}: {}", err.root_cause())
+                        }
+                        None => format!("Could not update the PATH: {}", err.root_cause()),
+                    });
+                    continue;
+                }
+            };
+
+            let of_shell = shell
+                .map(|shell| format!(" for {shell}"))
+                .unwrap_or_default();
+
+            match update_type {
                 UpdateType::Success => {
-                    println!(
-                        "{}",
+                    output.print(
                         format!(
-                            "✔ The installation directory {} has been added to your PATH",
+                            "✔ The installation directory {} has been added to your PATH{of_shell}",
                             install_dir.display()
                         )
-                        .green()
-                    );
-
-                    println!(
-                        "{}",
-                        format!("You may need to restart your terminal to apply the changes.")
-                            .green()
+                        .green(),
                     );
                 }
                 UpdateType::AlreadyInPath => {
-                    println!(
-                        "{}",
+                    output.print(
                         format!(
-                            "The installation directory {} is already in your PATH",
+                            "The installation directory {} is already in your PATH{of_shell}",
                             install_dir.display()
                         )
-                        .yellow()
+                        .yellow(),
                     );
                 }
-            },
-            Err(e) => {
-                return Err(anyhow::anyhow!(
-                    "Failed to update the PATH environment variable: {}",
-                    e
-                ));
+            };
+
+            if let Some(change) = installer.plan_path_update(shell) {
+                warn_if_skipped_by_login_shells(output, &change);
             }
+
+            path_updates.push(ShellPathUpdate {
+                shell,
+                outcome: update_type.into(),
+            });
+        }
+
+        let path_update = match path_updates
+            .iter()
+            .any(|update| matches!(update.outcome, PathUpdate::Success))
+        {
+            true => PathUpdate::Success,
+            false => PathUpdate::AlreadyInPath,
         };
 
-        Ok(())
+        if let PathUpdate::Success = path_update {
+            output.print("You may need to restart your terminal to apply the changes.".green());
+        }
+
+        if !failures.is_empty() {
+            return Err(InstallerError::PathUpdateFailed(anyhow!(failures.join("\n"))).into());
+        }
+
+        Ok((path_update, path_updates))
+    }
+
+    /// Returns the shells whose PATH to update without prompting the user
+    ///
+    /// These are the shells selected with `--shells`, or else the current
+    /// shell, as selected with `--shell` or detected along with the platform.
+    /// It is `None` if it could not be detected or on Windows, where the PATH
+    /// is not stored in the configuration of a shell.
+    fn selected_shells(&self, platform: &Platform) -> Vec<Option<Shell>> {
+        match &self.shells {
+            Some(shells) => shells.iter().copied().map(Some).collect(),
+            None => vec![self.shell.or(platform.shell)],
+        }
     }
 }
+
+/// Warns that login shells will not pick up a PATH update, because they read
+/// another file that does not source the updated one
+fn warn_if_skipped_by_login_shells(output: &Output, change: &PathChange) {
+    if let Some(login) = &change.skipped_by {
+        output.warn(format!(
+            "{login} does not source {target}, so login shells will not find Biome in the PATH; \
+             add `. {target}` to {login} to fix this",
+            target = change.target
+        ));
+    }
+}
+
+/// Returns whether a list of versions could not be fetched because its source
+/// does not have it, rather than because the request failed
+fn is_missing_list(err: &anyhow::Error) -> bool {
+    match InstallerError::find(err) {
+        Some(InstallerError::NotInCache(_)) => true,
+        Some(InstallerError::Network { source, .. }) => {
+            source.status() == Some(StatusCode::NOT_FOUND)
+        }
+        _ => false,
+    }
+}
+
+/// Builds the command that re-runs the installer with elevated rights
+fn elevated_command() -> String {
+    let program = std::env::current_exe()
+        .map(|program| program.display().to_string())
+        .unwrap_or_else(|_| "biome-installer".to_string());
+
+    let args: Vec<String> = std::env::args()
+        .skip(1)
+        .map(
+            |arg| match arg.contains(char::is_whitespace) || arg.contains('\'') {
+                true => format!("'{}'", arg.replace('\'', r"'\''")),
+                false => arg,
+            },
+        )
+        .collect();
+
+    format!("sudo {program} {}", args.join(" "))
+}
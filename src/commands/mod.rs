@@ -1,10 +1,64 @@
+mod bundle;
+mod cache;
+mod config;
 mod install;
+mod path;
+mod rollback;
 
+use std::time::Duration;
+
+use anyhow::Result;
+pub use bundle::BundleCommand;
+pub use cache::CacheCommand;
 use clap::Subcommand;
+pub use config::ConfigCommand;
 pub use install::InstallCommand;
+pub use path::PathCommand;
+pub use rollback::RollbackCommand;
+
+use crate::{
+    installer::{InstallLock, Installer},
+    output::Output,
+};
 
 #[derive(Subcommand)]
 
 pub enum Commands {
+    /// Download and install Biome
     Install(InstallCommand),
+
+    /// Restore the version of Biome that was installed before the last upgrade
+    Rollback(RollbackCommand),
+
+    /// Manage the cache of downloaded binaries
+    Cache(CacheCommand),
+
+    /// Create a bundle of binaries to install Biome without network access
+    Bundle(BundleCommand),
+
+    /// Manage the configuration files of the installer
+    Config(ConfigCommand),
+
+    /// Manage the PATH entries written to shell configuration files
+    Path(PathCommand),
+}
+
+/// Locks the installation directory, waiting for other installations
+///
+/// A spinner is shown while another run of the installer holds the lock.
+fn lock(output: &Output, installer: &Installer, timeout: u64) -> Result<InstallLock> {
+    let mut spinner = None;
+
+    let lock = installer.lock(Duration::from_secs(timeout), || {
+        spinner = Some(output.spinner(format!(
+            "Another installation into {} is in progress, waiting for it to finish...",
+            installer.install_dir().display()
+        )));
+    })?;
+
+    if let Some(spinner) = spinner {
+        spinner.success("The other installation has finished");
+    }
+
+    Ok(lock)
 }
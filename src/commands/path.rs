@@ -0,0 +1,76 @@
+use std::{env::consts::OS, path::PathBuf};
+
+use anyhow::{Result, bail};
+use clap::{Args, Subcommand, ValueHint, value_parser};
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::{
+    output::Output,
+    shell_config::{Removal, remove_path_entries},
+};
+
+#[derive(Args, Clone, Debug)]
+pub struct PathCommand {
+    #[command(subcommand)]
+    command: PathSubcommand,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum PathSubcommand {
+    /// Remove the lines that add Biome to the PATH from shell configuration files
+    Remove {
+        #[arg(
+            short,
+            long,
+            value_name = "DIR",
+            value_hint = ValueHint::DirPath,
+            value_parser = value_parser!(PathBuf),
+            help = "Only remove the lines that add this directory to the PATH, even if they were not written by the installer",
+        )]
+        install_dir: Option<PathBuf>,
+    },
+}
+
+/// The lines removed from shell configuration files
+#[derive(Serialize)]
+struct PathRemoveReport {
+    removed: Vec<Removal>,
+}
+
+impl PathCommand {
+    pub fn handle(&self, output: &Output) -> Result<()> {
+        match &self.command {
+            PathSubcommand::Remove { install_dir } => {
+                if OS == "windows" {
+                    bail!("Removing PATH entries is only supported on Linux and macOS");
+                }
+
+                let removed = remove_path_entries(install_dir.as_deref())?;
+
+                if removed.is_empty() {
+                    output.print("No shell configuration file adds Biome to the PATH");
+                }
+
+                for removal in &removed {
+                    for line in &removal.lines {
+                        output.print(
+                            format!(
+                                "✔ Removed `{line}` from {}",
+                                removal.file.display().to_string().bold()
+                            )
+                            .green(),
+                        );
+                    }
+
+                    output.print(format_args!(
+                        "The previous version of the file has been saved to {}",
+                        removal.backup.display()
+                    ));
+                }
+
+                output.report(&PathRemoveReport { removed })
+            }
+        }
+    }
+}
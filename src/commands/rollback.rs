@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Args, ValueHint, value_parser};
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::{
+    config::Config,
+    installer::{DEFAULT_LOCK_TIMEOUT, Installer, default_install_dir},
+    output::Output,
+};
+
+#[derive(Args, Clone, Debug)]
+pub struct RollbackCommand {
+    #[arg(
+        short,
+        long,
+        value_name = "DIR",
+        env = "BIOME_INSTALL_DIR",
+        value_hint = ValueHint::DirPath,
+        value_parser = value_parser!(PathBuf),
+        help = "The directory in which Biome is installed",
+    )]
+    install_dir: Option<PathBuf>,
+
+    /// How long to wait for another installation to finish
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        env = "BIOME_LOCK_TIMEOUT",
+        default_value_t = DEFAULT_LOCK_TIMEOUT,
+        help = "How long to wait for another installation into the same directory to finish",
+    )]
+    lock_timeout: u64,
+}
+
+/// The outcome of a rollback
+#[derive(Serialize)]
+struct RollbackReport {
+    version: String,
+    destination: PathBuf,
+    backup: PathBuf,
+}
+
+impl RollbackCommand {
+    /// Fills the values that were not specified with those of the
+    /// configuration files
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.install_dir = self.install_dir.or(config.install_dir);
+        self
+    }
+
+    pub fn handle(&self, output: &Output) -> Result<()> {
+        let install_dir = match &self.install_dir {
+            Some(dir) => dir.to_owned(),
+            None => default_install_dir()?,
+        };
+
+        let installer = Installer::new(install_dir);
+
+        let _lock = super::lock(output, &installer, self.lock_timeout)?;
+
+        let version = installer.rollback()?;
+
+        output.print(
+            format!(
+                "✔ Biome has been rolled back to {}",
+                version.as_str().bold()
+            )
+            .green(),
+        );
+
+        output.report(&RollbackReport {
+            version,
+            destination: installer.destination(),
+            backup: installer.backup(),
+        })
+    }
+}
@@ -0,0 +1,322 @@
+use std::{
+    env::consts::OS,
+    fs::{create_dir_all, read_to_string},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use home::home_dir;
+use serde::{Deserialize, Serialize};
+use toml_edit::DocumentMut;
+
+use crate::{downloader::DownloadSource, error::io_error, versions::VersionProviderKind};
+
+/// Defaults for the installer, read from configuration files
+///
+/// Two configuration files are read: a system-wide one, and one for the
+/// current user, whose values take precedence. Values set with command line
+/// flags or environment variables take precedence over both.
+///
+/// ```toml
+/// install_dir = "/opt/biome/bin"
+/// local_dir = ".biome/bin"
+/// channel = "stable"
+/// source = "github"
+/// download_mirror = "https://mirror.example.com/biome/releases"
+/// npm_registry = "https://registry.npmjs.org"
+/// versions_mirror = "https://mirror.example.com/biome/versions"
+/// version_provider = "auto"
+/// proxy = "http://proxy.example.com:3128"
+/// no_proxy = "localhost,.internal.example.com"
+/// ca_cert = "/etc/ssl/certs/proxy.pem"
+/// insecure = false
+/// connect_timeout = 10
+/// read_timeout = 30
+/// retries = 3
+/// prepend_path = false
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The directory in which to install Biome
+    pub install_dir: Option<PathBuf>,
+
+    /// The directory, relative to the project root, in which to install
+    /// Biome with `--local`
+    pub local_dir: Option<PathBuf>,
+
+    /// The release channel from which to pick the latest version
+    pub channel: Option<Channel>,
+
+    /// Where binaries are downloaded from
+    pub source: Option<DownloadSource>,
+
+    /// The base URL from which binaries are downloaded
+    pub download_mirror: Option<String>,
+
+    /// The URL of the npm registry from which packages are downloaded
+    pub npm_registry: Option<String>,
+
+    /// The base URL from which lists of versions are fetched
+    pub versions_mirror: Option<String>,
+
+    /// Where to look up the published versions of Biome
+    pub version_provider: Option<VersionProviderKind>,
+
+    /// The proxy through which to send requests
+    pub proxy: Option<String>,
+
+    /// The hosts to reach without the proxy
+    pub no_proxy: Option<String>,
+
+    /// A PEM file of additional CA certificates to trust
+    pub ca_cert: Option<PathBuf>,
+
+    /// Whether to skip the verification of TLS certificates
+    pub insecure: Option<bool>,
+
+    /// How long to wait for a connection to be established, in seconds
+    pub connect_timeout: Option<u64>,
+
+    /// How long to wait for data from the server, in seconds
+    pub read_timeout: Option<u64>,
+
+    /// How many times to retry failed requests
+    pub retries: Option<u32>,
+
+    /// Whether to prepend the installation directory to the PATH
+    pub prepend_path: Option<bool>,
+}
+
+/// A key of the configuration file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "snake_case")]
+pub enum ConfigKey {
+    InstallDir,
+    LocalDir,
+    Channel,
+    Source,
+    DownloadMirror,
+    NpmRegistry,
+    VersionsMirror,
+    VersionProvider,
+    Proxy,
+    NoProxy,
+    CaCert,
+    Insecure,
+    ConnectTimeout,
+    ReadTimeout,
+    Retries,
+    PrependPath,
+}
+
+/// A release channel of Biome
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    /// Stable releases only
+    #[default]
+    Stable,
+
+    /// Pre-releases, such as betas and release candidates
+    Preview,
+}
+
+impl Config {
+    /// Loads and merges the system-wide and user configuration files
+    ///
+    /// Missing configuration files are treated as empty.
+    pub fn load() -> Result<Self> {
+        let system = Self::read(&Self::system_file())?.unwrap_or_default();
+
+        let user = match Self::user_file() {
+            Ok(file) => Self::read(&file)?.unwrap_or_default(),
+            Err(_) => Config::default(),
+        };
+
+        Ok(user.or(system))
+    }
+
+    /// Reads the configuration file at the specified path, if it exists
+    pub fn read(path: &Path) -> Result<Option<Self>> {
+        let content = match read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(io_error(path)(err)),
+        };
+
+        let config = toml_edit::de::from_str(&content)
+            .with_context(|| format!("Invalid configuration file {}", path.display()))?;
+
+        Ok(Some(config))
+    }
+
+    /// Returns the path of the user configuration file
+    ///
+    /// This is the file specified by `BIOME_CONFIG`, or
+    /// `$XDG_CONFIG_HOME/biome-installer/config.toml` (by default
+    /// `~/.config/biome-installer/config.toml`), or
+    /// `%APPDATA%\biome-installer\config.toml` on Windows.
+    pub fn user_file() -> Result<PathBuf> {
+        if let Some(file) = std::env::var_os("BIOME_CONFIG") {
+            return Ok(PathBuf::from(file));
+        }
+
+        let home = home_dir().context("Could not determine the home directory")?;
+
+        let dir = match OS {
+            "windows" => std::env::var_os("APPDATA")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.join("AppData/Roaming")),
+            _ => std::env::var_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute())
+                .unwrap_or_else(|| home.join(".config")),
+        };
+
+        Ok(dir.join("biome-installer").join("config.toml"))
+    }
+
+    /// Returns the path of the system-wide configuration file
+    ///
+    /// This is `/etc/biome-installer/config.toml`, or
+    /// `%PROGRAMDATA%\biome-installer\config.toml` on Windows.
+    pub fn system_file() -> PathBuf {
+        match OS {
+            "windows" => std::env::var_os("PROGRAMDATA")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"))
+                .join("biome-installer")
+                .join("config.toml"),
+            _ => PathBuf::from("/etc/biome-installer/config.toml"),
+        }
+    }
+
+    /// Returns the value of the specified key, as JSON
+    pub fn get(&self, key: ConfigKey) -> serde_json::Value {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|config| config.get(key.name()).cloned())
+            .unwrap_or_default()
+    }
+
+    /// Fills the values missing from this configuration with those of another
+    fn or(self, fallback: Config) -> Config {
+        Config {
+            install_dir: self.install_dir.or(fallback.install_dir),
+            local_dir: self.local_dir.or(fallback.local_dir),
+            channel: self.channel.or(fallback.channel),
+            source: self.source.or(fallback.source),
+            download_mirror: self.download_mirror.or(fallback.download_mirror),
+            npm_registry: self.npm_registry.or(fallback.npm_registry),
+            versions_mirror: self.versions_mirror.or(fallback.versions_mirror),
+            version_provider: self.version_provider.or(fallback.version_provider),
+            proxy: self.proxy.or(fallback.proxy),
+            no_proxy: self.no_proxy.or(fallback.no_proxy),
+            ca_cert: self.ca_cert.or(fallback.ca_cert),
+            insecure: self.insecure.or(fallback.insecure),
+            connect_timeout: self.connect_timeout.or(fallback.connect_timeout),
+            read_timeout: self.read_timeout.or(fallback.read_timeout),
+            retries: self.retries.or(fallback.retries),
+            prepend_path: self.prepend_path.or(fallback.prepend_path),
+        }
+    }
+}
+
+impl ConfigKey {
+    /// Returns the name of the key in the configuration file
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConfigKey::InstallDir => "install_dir",
+            ConfigKey::LocalDir => "local_dir",
+            ConfigKey::Channel => "channel",
+            ConfigKey::Source => "source",
+            ConfigKey::DownloadMirror => "download_mirror",
+            ConfigKey::NpmRegistry => "npm_registry",
+            ConfigKey::VersionsMirror => "versions_mirror",
+            ConfigKey::VersionProvider => "version_provider",
+            ConfigKey::Proxy => "proxy",
+            ConfigKey::NoProxy => "no_proxy",
+            ConfigKey::CaCert => "ca_cert",
+            ConfigKey::Insecure => "insecure",
+            ConfigKey::ConnectTimeout => "connect_timeout",
+            ConfigKey::ReadTimeout => "read_timeout",
+            ConfigKey::Retries => "retries",
+            ConfigKey::PrependPath => "prepend_path",
+        }
+    }
+}
+
+/// Sets or removes a key of the configuration file at the specified path
+///
+/// The value is parsed as a TOML value, and is otherwise taken as a string.
+/// The resulting file is validated before being written, and comments and
+/// formatting of the existing file are preserved.
+pub fn edit(path: &Path, key: ConfigKey, value: Option<&str>) -> Result<()> {
+    let content = match read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(io_error(path)(err)),
+    };
+
+    let mut document: DocumentMut = content
+        .parse()
+        .with_context(|| format!("Invalid configuration file {}", path.display()))?;
+
+    match value {
+        Some(value) => {
+            let mut value = value
+                .parse::<toml_edit::Value>()
+                .unwrap_or_else(|_| value.to_string().into());
+
+            // Keep the comment that may follow the previous value
+            if let Some(previous) = document.get(key.name()).and_then(|item| item.as_value()) {
+                *value.decor_mut() = previous.decor().clone();
+            }
+
+            document[key.name()] = toml_edit::value(value);
+        }
+        None => {
+            document.remove(key.name());
+        }
+    }
+
+    let content = document.to_string();
+
+    toml_edit::de::from_str::<Config>(&content)
+        .with_context(|| format!("Invalid value for {}", key.name()))?;
+
+    let dir = path.parent().context("Invalid configuration file path")?;
+
+    create_dir_all(dir)
+        .map_err(io_error(dir))
+        .context("Could not create the configuration directory")?;
+
+    let mut staged = tempfile::Builder::new()
+        .prefix(".config-")
+        .tempfile_in(dir)
+        .map_err(io_error(dir))?;
+
+    staged.write_all(content.as_bytes())?;
+
+    staged
+        .persist(path)
+        .map_err(|err| io_error(path)(err.error))?;
+
+    Ok(())
+}
+
+/// Reads a boolean from the specified environment variable
+///
+/// As with the flags that clap reads from the environment, `0`, `false`,
+/// `no`, `off` and `n` are false, and any other non-empty value is true.
+pub fn env_flag(name: &str) -> Option<bool> {
+    let value = std::env::var(name).ok().filter(|value| !value.is_empty())?;
+
+    Some(!matches!(
+        value.to_lowercase().as_str(),
+        "0" | "false" | "no" | "off" | "n"
+    ))
+}
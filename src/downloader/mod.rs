@@ -1,59 +1,284 @@
 use std::{
-    env::consts::{ARCH, OS},
-    io::Write,
-    path::PathBuf,
+    fs::File,
+    io::{Write, copy},
+    path::{Path, PathBuf},
 };
 
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
 use semver::Version;
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
 
-use crate::platform::{Libc, Platform};
+use crate::{
+    bundle::Bundle,
+    cache::Cache,
+    error::{InstallerError, io_error},
+    http::Http,
+    npm::{NpmRegistry, binary_name, package_name},
+    platform::{Libc, Platform},
+};
+
+/// The names of all the assets published by Biome
+///
+/// These are all the names [Downloader::get_asset_name] can compute.
+pub const ASSET_NAMES: &[&str] = &[
+    "biome-linux-x64",
+    "biome-linux-x64-musl",
+    "biome-linux-arm64",
+    "biome-linux-arm64-musl",
+    "biome-darwin-x64",
+    "biome-darwin-arm64",
+    "biome-win32-x64.exe",
+    "biome-win32-arm64.exe",
+];
+
+/// The base URL from which binaries are downloaded by default
+pub const DEFAULT_DOWNLOAD_MIRROR: &str = "https://github.com/biomejs/biome/releases/download";
+
+/// Where binaries are downloaded from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadSource {
+    /// The assets of GitHub releases, or of a mirror of them
+    #[default]
+    Github,
+
+    /// The per-platform packages of an npm registry
+    Npm,
+}
 
 /// Biome Downloader
 pub struct Downloader {
     pub platform: Platform,
+    http: Http,
+    cache: Option<Cache>,
+    bundle: Option<Bundle>,
+    registry: Option<NpmRegistry>,
+    mirror: String,
+    offline: bool,
+}
+
+/// A downloaded binary
+pub struct Download {
+    /// The path to the temporary file holding the binary
+    pub path: PathBuf,
+
+    /// Whether the binary was reused from the cache
+    pub cached: bool,
 }
 
 impl Downloader {
-    /// Creates a new Downloader instance
-    pub fn new() -> Self {
+    /// Creates a new Downloader instance, sending requests with the
+    /// specified client
+    pub fn new(http: Http) -> Self {
         Self {
             platform: Platform::detect(),
+            http,
+            cache: None,
+            bundle: None,
+            registry: None,
+            mirror: DEFAULT_DOWNLOAD_MIRROR.to_string(),
+            offline: false,
         }
     }
 
+    /// Stores downloaded assets in, and reuses them from, the specified cache
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Selects the build of Biome for the specified libc implementation
+    pub fn with_libc(mut self, libc: Libc) -> Self {
+        self.platform.libc = libc;
+        self
+    }
+
+    /// Downloads binaries from the specified base URL instead of GitHub
+    ///
+    /// Binaries are expected at `<mirror>/<git tag>/<asset>`, like on GitHub.
+    pub fn with_mirror(mut self, mirror: &str) -> Self {
+        self.mirror = mirror.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Extracts binaries from the specified bundle instead of downloading them
+    pub fn with_bundle(mut self, bundle: Bundle) -> Self {
+        self.bundle = Some(bundle);
+        self
+    }
+
+    /// Downloads binaries from the packages of the specified npm registry
+    /// instead of GitHub
+    ///
+    /// The binaries are the same as those of GitHub releases, and share the
+    /// cache with them.
+    pub fn with_npm_registry(mut self, registry: NpmRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Returns the bundle binaries are extracted from, if any
+    pub fn bundle(&self) -> Option<&Bundle> {
+        self.bundle.as_ref()
+    }
+
+    /// Only uses the cache, without ever accessing the network
+    pub fn offline(mut self) -> Self {
+        self.offline = true;
+        self
+    }
+
     /// Downloads the specified version of Biome
     ///
     /// This function downloads the specified version of Biome into a temporary
-    /// file and returns the path to that file.
-    pub fn download(&self, version: Version) -> Result<PathBuf> {
-        let tag = self.get_git_tag(&version);
-
+    /// file in the given directory and returns the path to that file.
+    ///
+    /// The temporary file should be created in the installation directory,
+    /// so that it can later be moved into place with an atomic rename.
+    ///
+    /// When a cache is configured, a valid cached asset is used instead of
+    /// downloading it again, and freshly downloaded assets are cached. In
+    /// offline mode, an asset missing from the cache is an error.
+    ///
+    /// When a bundle is configured, the binary is extracted from it, and
+    /// neither the cache nor the network are used.
+    pub fn download(&self, version: Version, dir: &Path) -> Result<Download> {
         let asset = self
             .get_asset_name()
             .context("Could not compute asset name")?;
 
-        let url = format!("https://github.com/biomejs/biome/releases/download/{tag}/{asset}");
+        if let Some(bundle) = &self.bundle {
+            let entry = bundle
+                .find(Some(&version), &asset)
+                .ok_or(InstallerError::VersionNotFound(version))?;
 
-        let mut temp_file = tempfile::Builder::new()
-            .tempfile()
-            .context("Could not create temporary file")?;
+            return Ok(Download {
+                path: bundle.extract(entry, dir)?,
+                cached: false,
+            });
+        }
+
+        self.download_asset(version, &asset, dir)
+    }
 
-        let response = reqwest::blocking::get(&url)?;
+    /// Downloads the specified asset of the specified version of Biome
+    ///
+    /// This function behaves like [Self::download], but for an asset that
+    /// isn't necessarily meant for the current platform.
+    pub fn download_asset(&self, version: Version, asset: &str, dir: &Path) -> Result<Download> {
+        if let Some(cache) = &self.cache {
+            match cache.get(&version, asset) {
+                Ok(Some(cached)) => {
+                    let mut temp_file = self.temp_file(dir)?;
+
+                    copy(&mut File::open(&cached)?, temp_file.as_file_mut())
+                        .and_then(|_| temp_file.as_file().sync_all())
+                        .context("Failed to copy the cached binary")?;
+
+                    return Ok(Download {
+                        path: self.keep(temp_file)?,
+                        cached: true,
+                    });
+                }
+                Ok(None) => {}
+                // A corrupted asset is evicted, and downloaded again
+                Err(err)
+                    if matches!(
+                        InstallerError::find(&err),
+                        Some(InstallerError::ChecksumMismatch { .. })
+                    ) =>
+                {
+                    cache.remove(&version, asset)?
+                }
+                Err(err) => return Err(err),
+            }
+        }
 
-        if !response.status().is_success() {
-            return Err(anyhow!(
-                "Failed to download Biome from {url}: {}",
-                response.status()
-            ));
+        if self.offline {
+            return Err(InstallerError::NotInCache(format!("Biome {version} ({asset})")).into());
         }
 
-        let bytes = response.bytes().context("Failed to read response")?;
+        let mut temp_file = self.temp_file(dir)?;
+
+        match &self.registry {
+            Some(registry) => registry.download(
+                &package_name(asset),
+                &version,
+                binary_name(asset),
+                temp_file.as_file_mut(),
+            )?,
+            None => {
+                let url = self.get_asset_url(&version, asset);
+
+                let Some(bytes) = self.http.bytes(&url)? else {
+                    return Err(InstallerError::VersionNotFound(version).into());
+                };
+
+                temp_file
+                    .write_all(bytes.as_ref())
+                    .context("Failed to write to temporary file")?;
+            }
+        }
 
         temp_file
-            .write_all(bytes.as_ref())
+            .as_file()
+            .sync_all()
             .context("Failed to write to temporary file")?;
 
+        let path = self.keep(temp_file)?;
+
+        // Failing to populate the cache must not fail the installation
+        if let Some(cache) = &self.cache {
+            let _ = cache.store(&version, asset, &path);
+        }
+
+        Ok(Download {
+            path,
+            cached: false,
+        })
+    }
+
+    /// Checks whether the specified asset of the specified version can be
+    /// downloaded, without downloading it
+    ///
+    /// Assets in the cache or in the bundle are available without accessing
+    /// the network. Otherwise, the GitHub release (or the mirror) is checked
+    /// with a `HEAD` request, or the metadata of the npm package is read. In
+    /// offline mode, only the cache is checked.
+    pub fn has_asset(&self, version: &Version, asset: &str) -> Result<bool> {
+        if let Some(bundle) = &self.bundle {
+            return Ok(bundle.find(Some(version), asset).is_some());
+        }
+
+        if let Some(cache) = &self.cache
+            && let Ok(Some(_)) = cache.get(version, asset)
+        {
+            return Ok(true);
+        }
+
+        if self.offline {
+            return Ok(false);
+        }
+
+        match &self.registry {
+            Some(registry) => registry.has_version(&package_name(asset), version),
+            None => Ok(self.http.exists(&self.get_asset_url(version, asset))?),
+        }
+    }
+
+    /// Creates a temporary file in the specified directory
+    fn temp_file(&self, dir: &Path) -> Result<NamedTempFile> {
+        tempfile::Builder::new()
+            .prefix(".biome-")
+            .tempfile_in(dir)
+            .map_err(io_error(dir))
+            .context("Could not create temporary file")
+    }
+
+    /// Persists a temporary file, and returns its path
+    fn keep(&self, temp_file: NamedTempFile) -> Result<PathBuf> {
         let (_, path) = temp_file
             .keep()
             .context("Could not persist temporary file")?;
@@ -61,13 +286,42 @@ impl Downloader {
         Ok(path)
     }
 
+    /// Computes the URL from which the specified version can be downloaded
+    ///
+    /// For a bundle, this is the path of the bundle followed by the path of
+    /// the binary inside it, e.g. `biome-bundle.tar.gz#2.0.6/biome-linux-x64`.
+    pub fn get_download_url(&self, version: &Version) -> Result<String> {
+        let asset = self
+            .get_asset_name()
+            .context("Could not compute asset name")?;
+
+        match &self.bundle {
+            Some(bundle) => Ok(format!("{}#{version}/{asset}", bundle.path().display())),
+            None => Ok(self.get_asset_url(version, &asset)),
+        }
+    }
+
+    /// Computes the URL of the specified asset of the specified version
+    ///
+    /// For an npm registry, this is the URL of the tarball of the package
+    /// holding the asset.
+    pub fn get_asset_url(&self, version: &Version, asset: &str) -> String {
+        if let Some(registry) = &self.registry {
+            return registry.tarball_url(&package_name(asset), version);
+        }
+
+        let tag = self.get_git_tag(version);
+
+        format!("{}/{tag}/{asset}", self.mirror)
+    }
+
     /// Computes the git tag for the specified version
     ///
     /// This function computes the git tag for the specified version of Biome.
     ///
     /// Versions prior to 2.0.0 use the `cli/vX.Y.Z` format, while versions
     /// 2.0.0 and later use the `@biomejs/biome@X.Y.Z` format
-    fn get_git_tag(&self, version: &Version) -> String {
+    pub fn get_git_tag(&self, version: &Version) -> String {
         match version.major {
             1 => format!("cli/v{version}"),
             _ => format!("@biomejs/biome@{version}"),
@@ -82,7 +336,7 @@ impl Downloader {
     /// The assets published by Biome use Node.js naming conventions with
     /// regard to the operating system, architecture, so we need to map
     /// the rust naming conventions to the Node.js naming conventions.
-    fn get_asset_name(&self) -> Result<String> {
+    pub fn get_asset_name(&self) -> Result<String> {
         let asset = match (
             self.platform.os.as_str(),
             self.platform.arch.as_str(),
@@ -96,12 +350,11 @@ impl Downloader {
             ("macos", "aarch64", _) => "biome-darwin-arm64",
             ("windows", "x86_64", _) => "biome-win32-x64.exe",
             ("windows", "aarch64", _) => "biome-win32-arm64.exe",
-            _ => Err(anyhow!(
-                "Unsupported platform: {} {} {}",
-                OS,
-                ARCH,
-                self.platform.libc
-            ))?,
+            (os, arch, libc) => Err(InstallerError::UnsupportedPlatform {
+                os: os.to_string(),
+                arch: arch.to_string(),
+                libc: libc.to_string(),
+            })?,
         };
 
         Ok(asset.to_string())
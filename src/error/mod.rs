@@ -0,0 +1,227 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use inquire::InquireError;
+use semver::Version;
+use thiserror::Error;
+
+/// The exit codes of the installer, as displayed in the help message
+pub const EXIT_CODES: &str = "\
+Exit codes:
+  0    Success
+  1    Unexpected error
+  2    Invalid command-line arguments
+  3    Network failure
+  4    Version not found
+  5    Unsupported platform
+  6    Checksum mismatch
+  7    Permission denied
+  8    PATH update failed
+  9    Installed binary does not work
+  10   Another installation is in progress
+  11   Not available in the cache in offline mode
+  12   TLS certificate verification failed
+  13   Rate limit exceeded
+  14   No build for the current platform
+  130  Cancelled by the user";
+
+/// Errors that cause the installer to fail
+///
+/// Each variant maps to a stable code, used in JSON output, and to a
+/// distinct exit code, so that wrapper scripts can react to failures
+/// without parsing error messages. Any other error is reported with the
+/// `internal_error` code and exits with status 1.
+#[derive(Debug, Error)]
+pub enum InstallerError {
+    /// The command-line arguments could not be parsed
+    #[error("{}", invalid_arguments_message(.0))]
+    InvalidArguments(clap::Error),
+
+    /// A network request failed
+    #[error("Network request to {url} failed")]
+    Network {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    /// The certificate of a server could not be verified
+    #[error("Could not verify the TLS certificate of {url}: {hint}")]
+    Tls {
+        url: String,
+        hint: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    /// A server, usually GitHub, refused a request because the rate limit was
+    /// exceeded
+    #[error("Rate limit exceeded for {url}: {hint}")]
+    RateLimited { url: String, hint: String },
+
+    /// The requested version of Biome does not exist
+    #[error("Biome {0} could not be found")]
+    VersionNotFound(Version),
+
+    /// The requested version of Biome has no build for the current platform
+    #[error(
+        "Biome {version} has no build for {platform}{}",
+        closest
+            .as_ref()
+            .map(|closest| format!("; the closest version that does is {closest}"))
+            .unwrap_or_default()
+    )]
+    AssetNotFound {
+        version: Version,
+        platform: String,
+        closest: Option<Version>,
+    },
+
+    /// Biome does not provide a binary for the current platform
+    #[error("Unsupported platform: {os} {arch} {libc}")]
+    UnsupportedPlatform {
+        os: String,
+        arch: String,
+        libc: String,
+    },
+
+    /// A downloaded file does not match its expected checksum
+    #[error("Checksum mismatch for {file}: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        file: String,
+        expected: String,
+        actual: String,
+    },
+
+    /// The installer is not allowed to write to a path
+    #[error("Permission denied: {}", path.display())]
+    PermissionDenied {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    /// The installation directory could not be added to the PATH
+    #[error("Failed to update the PATH environment variable")]
+    PathUpdateFailed(#[source] anyhow::Error),
+
+    /// The installed binary does not run, or reports an unexpected version
+    ///
+    /// `unloadable` is set when the binary could not be loaded at all, as
+    /// happens when it was built for another libc or architecture.
+    #[error("The installed binary at {} does not work: {reason}", path.display())]
+    BrokenBinary {
+        path: PathBuf,
+        reason: String,
+        unloadable: bool,
+    },
+
+    /// Another run of the installer holds the lock on the installation directory
+    #[error(
+        "Another installation into {} is still in progress after {timeout} seconds",
+        path.display()
+    )]
+    InstallInProgress { path: PathBuf, timeout: u64 },
+
+    /// Something required in offline mode is missing from the cache
+    #[error("{0} is not in the cache, and cannot be downloaded in offline mode")]
+    NotInCache(String),
+
+    /// The user cancelled a prompt
+    #[error("The installation was cancelled")]
+    Cancelled,
+}
+
+impl InstallerError {
+    /// Returns the stable, machine-readable code of the error
+    pub fn code(&self) -> &'static str {
+        match self {
+            InstallerError::InvalidArguments(_) => "invalid_arguments",
+            InstallerError::Network { .. } => "network_error",
+            InstallerError::VersionNotFound(_) => "version_not_found",
+            InstallerError::UnsupportedPlatform { .. } => "unsupported_platform",
+            InstallerError::ChecksumMismatch { .. } => "checksum_mismatch",
+            InstallerError::PermissionDenied { .. } => "permission_denied",
+            InstallerError::PathUpdateFailed(_) => "path_update_failed",
+            InstallerError::BrokenBinary { .. } => "broken_binary",
+            InstallerError::InstallInProgress { .. } => "install_in_progress",
+            InstallerError::NotInCache(_) => "not_in_cache",
+            InstallerError::Tls { .. } => "tls_error",
+            InstallerError::RateLimited { .. } => "rate_limited",
+            InstallerError::AssetNotFound { .. } => "asset_not_found",
+            InstallerError::Cancelled => "user_cancelled",
+        }
+    }
+
+    /// Returns the exit code of the process when failing with this error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            InstallerError::InvalidArguments(_) => 2,
+            InstallerError::Network { .. } => 3,
+            InstallerError::VersionNotFound(_) => 4,
+            InstallerError::UnsupportedPlatform { .. } => 5,
+            InstallerError::ChecksumMismatch { .. } => 6,
+            InstallerError::PermissionDenied { .. } => 7,
+            InstallerError::PathUpdateFailed(_) => 8,
+            InstallerError::BrokenBinary { .. } => 9,
+            InstallerError::InstallInProgress { .. } => 10,
+            InstallerError::NotInCache(_) => 11,
+            InstallerError::Tls { .. } => 12,
+            InstallerError::RateLimited { .. } => 13,
+            InstallerError::AssetNotFound { .. } => 14,
+            InstallerError::Cancelled => 130,
+        }
+    }
+
+    /// Finds the first typed error in the chain of the given error
+    pub fn find(err: &anyhow::Error) -> Option<&InstallerError> {
+        err.chain().find_map(|cause| cause.downcast_ref())
+    }
+}
+
+/// Extracts the message of a command-line error, without the usage that clap
+/// prints along with it
+fn invalid_arguments_message(err: &clap::Error) -> String {
+    let rendered = err.to_string();
+    let message = rendered.lines().next().unwrap_or_default();
+
+    message.trim_start_matches("error: ").to_string()
+}
+
+/// Converts a failed request to the given URL into an installer error
+pub fn network_error(url: &str) -> impl FnOnce(reqwest::Error) -> InstallerError {
+    move |source| InstallerError::Network {
+        url: url.to_string(),
+        source,
+    }
+}
+
+/// Converts an I/O error on the given path into an installer error
+///
+/// Permission errors are reported as [InstallerError::PermissionDenied],
+/// while any other error is passed through.
+pub fn io_error(path: &Path) -> impl FnOnce(io::Error) -> anyhow::Error {
+    move |source| match source.kind() {
+        io::ErrorKind::PermissionDenied => InstallerError::PermissionDenied {
+            path: path.to_path_buf(),
+            source,
+        }
+        .into(),
+        _ => source.into(),
+    }
+}
+
+/// Converts a prompt error into an installer error
+///
+/// Pressing `Esc` or `Ctrl+C` is reported as [InstallerError::Cancelled],
+/// while any other error is passed through.
+pub fn prompt_error(err: InquireError) -> anyhow::Error {
+    match err {
+        InquireError::OperationCanceled | InquireError::OperationInterrupted => {
+            InstallerError::Cancelled.into()
+        }
+        err => err.into(),
+    }
+}
@@ -0,0 +1,550 @@
+use std::{
+    fs::read,
+    path::PathBuf,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use clap::{ArgAction, Args, ValueHint, value_parser};
+use reqwest::{
+    Certificate, Method, NoProxy, Proxy, StatusCode, Url,
+    blocking::{Client, ClientBuilder, Response},
+    header::{AUTHORIZATION, HeaderMap, LOCATION, RETRY_AFTER},
+    redirect::Policy,
+};
+
+use crate::{
+    config::{Config, env_flag},
+    error::{InstallerError, io_error, network_error},
+};
+
+/// The User-Agent sent with every request
+const USER_AGENT: &str = concat!("biome-installer/", env!("CARGO_PKG_VERSION"));
+
+/// How long to wait for a connection to be established by default
+pub const DEFAULT_CONNECT_TIMEOUT: u64 = 10;
+
+/// How long to wait for data from the server by default
+pub const DEFAULT_READ_TIMEOUT: u64 = 30;
+
+/// The longest delay before a failed request is retried, which is also the
+/// longest backoff
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(32);
+
+/// How many times failed requests are retried by default
+pub const DEFAULT_RETRIES: u32 = 3;
+
+/// The hosts to which the GitHub token may be sent
+const GITHUB_HOSTS: &[&str] = &["github.com", "api.github.com"];
+
+/// The environment variables from which proxies are read by default
+const PROXY_VARIABLES: &[&str] = &[
+    "HTTPS_PROXY",
+    "https_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+];
+
+/// Options of the connections to the network
+#[derive(Args, Clone, Debug, Default)]
+pub struct HttpOptions {
+    #[arg(
+        long,
+        value_name = "URL",
+        env = "BIOME_PROXY",
+        value_hint = ValueHint::Url,
+        help = "The proxy through which to send requests, instead of the system proxy",
+        help_heading = "Network",
+    )]
+    proxy: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "HOSTS",
+        env = "BIOME_NO_PROXY",
+        help = "A comma-separated list of hosts to reach without the proxy, or * for all",
+        help_heading = "Network"
+    )]
+    no_proxy: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PEM",
+        env = "BIOME_CA_CERT",
+        value_hint = ValueHint::FilePath,
+        value_parser = value_parser!(PathBuf),
+        help = "A PEM file of additional CA certificates to trust, such as the one of a proxy",
+        help_heading = "Network",
+    )]
+    ca_cert: Option<PathBuf>,
+
+    /// Do not verify TLS certificates
+    #[arg(
+        long,
+        action = ArgAction::SetTrue,
+        overrides_with = "no_insecure",
+        help = "Do not verify TLS certificates (dangerous, only use as a last resort), also read from BIOME_INSECURE",
+        help_heading = "Network",
+    )]
+    insecure: bool,
+
+    #[arg(
+        long,
+        action = ArgAction::SetTrue,
+        overrides_with = "insecure",
+        help = "Verify TLS certificates, even if BIOME_INSECURE or the configuration disables it",
+        help_heading = "Network",
+    )]
+    no_insecure: bool,
+
+    /// How the verification of TLS certificates was disabled, once the flags
+    /// have been merged with the environment and the configuration
+    #[arg(skip)]
+    insecure_source: Option<&'static str>,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        env = "BIOME_CONNECT_TIMEOUT",
+        help = format!("How long to wait for a connection to be established [default: {DEFAULT_CONNECT_TIMEOUT}]"),
+        help_heading = "Network",
+    )]
+    connect_timeout: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        env = "BIOME_READ_TIMEOUT",
+        help = format!("How long to wait for data from the server before giving up on a stalled request [default: {DEFAULT_READ_TIMEOUT}]"),
+        help_heading = "Network",
+    )]
+    read_timeout: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        env = "BIOME_RETRIES",
+        help = format!("How many times to retry failed requests [default: {DEFAULT_RETRIES}]"),
+        help_heading = "Network",
+    )]
+    retries: Option<u32>,
+
+    #[arg(
+        long,
+        value_name = "TOKEN",
+        env = "GITHUB_TOKEN",
+        hide_env_values = true,
+        help = "A GitHub token to authenticate requests to GitHub with, also read from GH_TOKEN",
+        help_heading = "Network"
+    )]
+    github_token: Option<String>,
+}
+
+/// The HTTP client shared by every request of the installer
+///
+/// Connections are reused across requests, every request identifies the
+/// installer with its User-Agent, and requests that fail because of a
+/// transient error are retried with an exponential backoff.
+#[derive(Clone)]
+pub struct Http {
+    client: Client,
+
+    /// The client used for requests that carry the GitHub token, which
+    /// doesn't follow redirects
+    github: Client,
+
+    /// How many times failed requests are retried
+    retries: u32,
+
+    /// The token sent to GitHub
+    github_token: Option<String>,
+
+    /// The proxy requests are sent through, if known
+    proxy: Option<String>,
+}
+
+impl HttpOptions {
+    /// Fills the values that were not specified with those of the
+    /// configuration files
+    pub fn with_config(mut self, config: &Config) -> Self {
+        self.proxy = self.proxy.or(config.proxy.clone());
+        self.no_proxy = self.no_proxy.or(config.no_proxy.clone());
+        self.ca_cert = self.ca_cert.or(config.ca_cert.clone());
+        self.insecure_source = match (self.insecure, self.no_insecure) {
+            (true, _) => Some("with --insecure"),
+            (_, true) => None,
+            _ => match env_flag("BIOME_INSECURE") {
+                Some(insecure) => insecure.then_some("with BIOME_INSECURE"),
+                None => (config.insecure == Some(true)).then_some("in the configuration"),
+            },
+        };
+        self.connect_timeout = self.connect_timeout.or(config.connect_timeout);
+        self.read_timeout = self.read_timeout.or(config.read_timeout);
+        self.retries = self.retries.or(config.retries);
+        self
+    }
+
+    /// Returns how the verification of TLS certificates was disabled, e.g.
+    /// "with --insecure", or `None` if certificates are verified
+    pub fn insecure_source(&self) -> Option<&'static str> {
+        self.insecure_source
+    }
+
+    /// Builds the HTTP client
+    ///
+    /// Without any option, the proxy is read from the environment or from
+    /// the system settings, as reqwest does by default. As soon as a proxy or
+    /// a list of hosts to reach without it is specified, the proxy is
+    /// configured explicitly instead.
+    pub fn client(&self) -> Result<Http> {
+        let builder = self.builder()?;
+
+        let github_token = self
+            .github_token
+            .clone()
+            .or_else(|| std::env::var("GH_TOKEN").ok())
+            .filter(|token| !token.trim().is_empty());
+
+        Ok(Http {
+            client: builder
+                .build()
+                .context("Could not initialize the HTTP client")?,
+            github: self
+                .builder()?
+                .redirect(Policy::none())
+                .build()
+                .context("Could not initialize the HTTP client")?,
+            retries: self.retries.unwrap_or(DEFAULT_RETRIES),
+            github_token,
+            proxy: self.proxy(),
+        })
+    }
+
+    /// Returns the proxy requests are sent through, if known
+    fn proxy(&self) -> Option<String> {
+        match (&self.proxy, &self.no_proxy) {
+            (_, Some(hosts)) if hosts.trim() == "*" => None,
+            (Some(proxy), _) => Some(proxy.clone()),
+            (None, _) => PROXY_VARIABLES
+                .iter()
+                .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty())),
+        }
+    }
+
+    /// Configures a builder of HTTP clients with these options
+    ///
+    /// The blocking client applies its timeout to the wait for the response,
+    /// and then to each read of the body, which is always streamed with
+    /// [read_body]. It is thus a stall timeout, and a slow download that keeps
+    /// making progress is never interrupted.
+    fn builder(&self) -> Result<ClientBuilder> {
+        let mut builder = Client::builder()
+            .user_agent(USER_AGENT)
+            .connect_timeout(Duration::from_secs(
+                self.connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+            ))
+            .timeout(Duration::from_secs(
+                self.read_timeout.unwrap_or(DEFAULT_READ_TIMEOUT),
+            ));
+
+        match (&self.no_proxy, self.proxy()) {
+            (Some(hosts), _) if hosts.trim() == "*" => builder = builder.no_proxy(),
+            (None, _) if self.proxy.is_none() => {}
+            (no_proxy, Some(url)) => {
+                let no_proxy = match no_proxy {
+                    Some(hosts) => NoProxy::from_string(hosts),
+                    None => NoProxy::from_env(),
+                };
+
+                builder = builder.proxy(
+                    Proxy::all(&url)
+                        .with_context(|| format!("Invalid proxy URL: {url}"))?
+                        .no_proxy(no_proxy),
+                );
+            }
+            (_, None) => {}
+        }
+
+        if let Some(path) = &self.ca_cert {
+            let pem = read(path)
+                .map_err(io_error(path))
+                .with_context(|| format!("Could not read the CA certificate {}", path.display()))?;
+
+            let certificates = Certificate::from_pem_bundle(&pem)
+                .with_context(|| format!("Invalid CA certificate {}", path.display()))?;
+
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if self.insecure_source.is_some() {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        Ok(builder)
+    }
+}
+
+impl Http {
+    /// Downloads the content of the specified URL
+    ///
+    /// It returns `None` if the server responds with `404 Not Found`.
+    pub fn bytes(&self, url: &str) -> Result<Option<Vec<u8>>, InstallerError> {
+        self.request(Method::GET, url, |response| match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            _ => read_body(response.error_for_status()?).map(Some),
+        })
+    }
+
+    /// Downloads the content of the specified URL as text
+    pub fn text(&self, url: &str) -> Result<String, InstallerError> {
+        self.request(Method::GET, url, |response| {
+            let body = read_body(response.error_for_status()?)?;
+
+            Ok(String::from_utf8_lossy(&body).into_owned())
+        })
+    }
+
+    /// Checks whether the specified URL exists, with a `HEAD` request
+    ///
+    /// Servers that don't support `HEAD` requests are assumed to serve the
+    /// URL, so that the download itself reports whether it exists.
+    pub fn exists(&self, url: &str) -> Result<bool, InstallerError> {
+        self.request(Method::HEAD, url, |response| match response.status() {
+            StatusCode::NOT_FOUND => Ok(false),
+            StatusCode::METHOD_NOT_ALLOWED => Ok(true),
+            _ => response.error_for_status().map(|_| true),
+        })
+    }
+
+    /// Sends a request to the specified URL and reads the response
+    ///
+    /// Requests that time out, fail to connect, or get a `429 Too Many
+    /// Requests` or `5xx` response are retried, after waiting for the delay
+    /// requested by the server with `Retry-After`, or otherwise for 1, 2, 4,
+    /// ... seconds. Requests for which the server asks to wait longer than
+    /// [MAX_RETRY_DELAY] are not retried.
+    fn request<T>(
+        &self,
+        method: Method,
+        url: &str,
+        read: impl Fn(Response) -> reqwest::Result<T>,
+    ) -> Result<T, InstallerError> {
+        let mut attempt = 0;
+
+        loop {
+            let backoff = Duration::from_secs(1 << attempt.min(5));
+
+            let (result, retry_after, rate_limit) = match self.send(method.clone(), url) {
+                Ok(response) => {
+                    let retry_after = retry_after(&response);
+                    let rate_limit = RateLimit::read(response.headers());
+
+                    (read(response), retry_after, rate_limit)
+                }
+                Err(err) => (Err(err), None, None),
+            };
+
+            let err = match result {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+
+            if let Some(rate_limit) = rate_limit
+                && rate_limit.remaining == 0
+                && matches!(
+                    err.status(),
+                    Some(StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS)
+                )
+            {
+                return Err(InstallerError::RateLimited {
+                    url: url.to_string(),
+                    hint: rate_limit.hint(self.github_token.is_some()),
+                });
+            }
+
+            let is_retryable =
+                err.is_timeout() || err.is_connect() || err.status().is_some_and(is_transient);
+
+            if !is_retryable || attempt >= self.retries || is_certificate_error(&err) {
+                return Err(self.error(url, err));
+            }
+
+            // Don't wait longer than the backoff ever would, since the server
+            // is unlikely to recover while the installer waits
+            if let Some(delay) = retry_after
+                && delay > MAX_RETRY_DELAY
+            {
+                return Err(match err.status() {
+                    Some(StatusCode::TOO_MANY_REQUESTS) => InstallerError::RateLimited {
+                        url: url.to_string(),
+                        hint: format!(
+                            "the server asks to retry in {} seconds, try again later",
+                            delay.as_secs()
+                        ),
+                    },
+                    _ => self.error(url, err),
+                });
+            }
+
+            attempt += 1;
+            thread::sleep(retry_after.unwrap_or(backoff));
+        }
+    }
+
+    /// Sends a request to the specified URL
+    ///
+    /// The GitHub token is only sent to GitHub over HTTPS. Those requests
+    /// don't follow redirects automatically: the redirect is followed without
+    /// the token, so that it never reaches the CDN hosting release assets.
+    fn send(&self, method: Method, url: &str) -> reqwest::Result<Response> {
+        let token = self.github_token.as_ref().filter(|_| is_github(url));
+
+        let Some(token) = token else {
+            return self.client.request(method, url).send();
+        };
+
+        let response = self
+            .github
+            .request(method.clone(), url)
+            .header(AUTHORIZATION, format!("Bearer {token}"))
+            .send()?;
+
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| response.url().join(location).ok());
+
+        match (response.status().is_redirection(), location) {
+            (true, Some(location)) => self.client.request(method, location).send(),
+            _ => Ok(response),
+        }
+    }
+
+    /// Converts a failed request into an installer error
+    fn error(&self, url: &str, err: reqwest::Error) -> InstallerError {
+        if !is_certificate_error(&err) {
+            return network_error(url)(err);
+        }
+
+        let hint = match &self.proxy {
+            Some(proxy) => format!(
+                "the proxy {proxy} probably intercepts TLS connections, \
+                 pass its CA certificate with --ca-cert"
+            ),
+            None => "if you are behind a proxy that intercepts TLS connections, \
+                     pass its CA certificate with --ca-cert"
+                .to_string(),
+        };
+
+        InstallerError::Tls {
+            url: url.to_string(),
+            hint,
+            source: err,
+        }
+    }
+}
+
+/// The rate limit reported by GitHub in the headers of a response
+struct RateLimit {
+    limit: Option<u64>,
+    remaining: u64,
+    reset: Option<u64>,
+}
+
+impl RateLimit {
+    /// Reads the `x-ratelimit-*` headers of a response, if any
+    fn read(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| -> Option<u64> { headers.get(name)?.to_str().ok()?.parse().ok() };
+
+        Some(RateLimit {
+            limit: header("x-ratelimit-limit"),
+            remaining: header("x-ratelimit-remaining")?,
+            reset: header("x-ratelimit-reset"),
+        })
+    }
+
+    /// Describes when the limit resets, and how to raise it
+    fn hint(&self, authenticated: bool) -> String {
+        let mut hint = match self.limit {
+            Some(limit) => format!("all {limit} requests allowed per hour have been used"),
+            None => "all requests allowed per hour have been used".to_string(),
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or_default();
+
+        if let Some(reset) = self.reset {
+            let minutes = reset.saturating_sub(now).div_ceil(60);
+            hint.push_str(&format!(", the limit resets in {minutes} minutes"));
+        }
+
+        if !authenticated {
+            hint.push_str(", set GITHUB_TOKEN or pass --github-token to raise the limit");
+        }
+
+        hint
+    }
+}
+
+/// Checks whether the GitHub token may be sent to the specified URL
+fn is_github(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|url| {
+        url.scheme() == "https"
+            && url
+                .host_str()
+                .is_some_and(|host| GITHUB_HOSTS.contains(&host))
+    })
+}
+
+/// Checks whether a response status is worth retrying the request for
+fn is_transient(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Reads the body of a response
+///
+/// The body is streamed rather than read at once, so that the timeout of the
+/// client applies to each read rather than to the whole body.
+fn read_body(mut response: Response) -> reqwest::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    response.copy_to(&mut body)?;
+
+    Ok(body)
+}
+
+/// Reads the delay requested by the server before retrying, in seconds
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Checks whether a request failed because a certificate could not be verified
+fn is_certificate_error(err: &reqwest::Error) -> bool {
+    let mut source: Option<&dyn std::error::Error> = Some(err);
+
+    while let Some(err) = source {
+        let message = err.to_string().to_lowercase();
+
+        if message.contains("certificate") || message.contains("self signed") {
+            return true;
+        }
+
+        source = err.source();
+    }
+
+    false
+}
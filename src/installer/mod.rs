@@ -1,11 +1,102 @@
-use anyhow::{Context, Result};
-use pathman::{PathmanError, UpdateType, prepend_to_path};
+use anyhow::{Context, Result, bail};
+use fs4::fs_std::FileExt;
+use home::home_dir;
+use pathman::UpdateType;
+use semver::Version;
+use serde::Serialize;
 use std::{
-    fs::{create_dir_all, rename},
-    path::PathBuf,
+    env::consts::OS,
+    ffi::OsString,
+    fs::{File, OpenOptions, copy, create_dir_all, read_to_string, remove_file, rename, write},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::Command,
+    thread,
+    time::{Duration, Instant},
 };
+use tempfile::NamedTempFile;
 
-use crate::platform::Platform;
+use crate::{
+    error::{InstallerError, io_error},
+    platform::{Platform, Shell},
+    shell_config,
+};
+
+/// A change to the PATH environment variable
+#[derive(Clone, Serialize)]
+pub struct PathChange {
+    /// The shell configuration file, or registry value, that would be updated
+    pub target: String,
+
+    /// The line, or PATH entry, that would be added
+    pub line: String,
+
+    /// Whether the line is already present, in which case nothing is written
+    pub already_present: bool,
+
+    /// The file read by login shells instead of the target, when it does not
+    /// source the target
+    pub skipped_by: Option<String>,
+}
+
+/// Returns the default installation directory, `~/.biome/bin`
+pub fn default_install_dir() -> Result<PathBuf> {
+    Ok(home_dir()
+        .context("Could not determine the home directory")?
+        .join(".biome")
+        .join("bin"))
+}
+
+/// The directory in which Biome is installed by default with `--system`
+pub const SYSTEM_INSTALL_DIR: &str = "/usr/local/bin";
+
+/// Returns the file through which a system-wide installation directory is
+/// added to the PATH of every user
+///
+/// This is `/etc/paths.d/biome` on macOS, which login shells read with
+/// `path_helper`, and `/etc/profile.d/biome.sh` elsewhere.
+pub fn system_profile() -> PathBuf {
+    match OS {
+        "macos" => PathBuf::from("/etc/paths.d/biome"),
+        _ => PathBuf::from("/etc/profile.d/biome.sh"),
+    }
+}
+
+/// Checks that the installer is allowed to write into the specified directory
+///
+/// A directory that does not exist yet is checked through its closest
+/// existing parent, in which it would be created.
+pub fn check_writable(dir: &Path) -> Result<()> {
+    let existing = dir
+        .ancestors()
+        .find(|ancestor| ancestor.exists())
+        .unwrap_or(dir);
+
+    tempfile::Builder::new()
+        .prefix(".biome-")
+        .tempfile_in(existing)
+        .map_err(io_error(existing))?;
+
+    Ok(())
+}
+
+/// How long to wait for another installation to finish by default
+pub const DEFAULT_LOCK_TIMEOUT: u64 = 300;
+
+/// An advisory lock on the installation directory
+///
+/// The lock is released when this value is dropped.
+pub struct InstallLock {
+    _file: File,
+}
+
+/// The error number of an executable format error, which is the same on
+/// every Unix-like system
+const ENOEXEC: i32 = 8;
+
+/// The directory, inside the installation directory, in which the previous
+/// binary is kept
+const BACKUP_DIR: &str = ".biome-backup";
 
 pub struct Installer {
     platform: Platform,
@@ -21,43 +112,478 @@ impl Installer {
         }
     }
 
+    /// Returns the directory in which Biome is installed
+    pub fn install_dir(&self) -> &Path {
+        &self.install_dir
+    }
+
+    /// Ensures that the installation directory exists
+    pub fn prepare(&self) -> Result<()> {
+        create_dir_all(&self.install_dir)
+            .map_err(io_error(&self.install_dir))
+            .context("Could not create the installation directory")
+    }
+
+    /// Locks the installation directory
+    ///
+    /// This function takes an advisory lock on a file dedicated to the
+    /// installation directory, so that concurrent runs of the installer don't
+    /// race on the binary or on the shell configuration file. If another run
+    /// holds the lock, `on_wait` is called once and the lock is retried until
+    /// the timeout expires.
+    ///
+    /// The installation directory must exist. See [Self::lock_file] for the
+    /// location of the lock file.
+    pub fn lock(&self, timeout: Duration, on_wait: impl FnOnce()) -> Result<InstallLock> {
+        let path = self.lock_file()?;
+
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(io_error(&path))
+            .context("Could not open the lock file")?;
+
+        let started = Instant::now();
+        let mut on_wait = Some(on_wait);
+
+        loop {
+            if file
+                .try_lock_exclusive()
+                .map_err(io_error(&path))
+                .context("Could not lock the installation directory")?
+            {
+                return Ok(InstallLock { _file: file });
+            }
+
+            if started.elapsed() >= timeout {
+                return Err(InstallerError::InstallInProgress {
+                    path: self.install_dir.clone(),
+                    timeout: timeout.as_secs(),
+                }
+                .into());
+            }
+
+            if let Some(on_wait) = on_wait.take() {
+                on_wait();
+            }
+
+            thread::sleep(Duration::from_millis(200));
+        }
+    }
+
+    /// Returns the path of the lock file of the installation directory
+    ///
+    /// The lock file lives next to the installation directory, rather than
+    /// in it, since the installation directory may be on the PATH. It is
+    /// named after the canonical path of the directory, so that every way of
+    /// referring to it shares the same lock, e.g.
+    /// `~/.biome/.biome-installer-bin.lock` for `~/.biome/bin`.
+    fn lock_file(&self) -> Result<PathBuf> {
+        let dir = self
+            .install_dir
+            .canonicalize()
+            .map_err(io_error(&self.install_dir))
+            .context("Could not lock the installation directory")?;
+
+        match (dir.parent(), dir.file_name()) {
+            (Some(parent), Some(name)) => {
+                let mut file = OsString::from(".biome-installer-");
+                file.push(name);
+                file.push(".lock");
+
+                Ok(parent.join(file))
+            }
+            _ => Ok(dir.join(".biome-installer.lock")),
+        }
+    }
+
     /// Installs the Biome binary to the specified directory
     ///
-    /// This functions ensures that the installation directory exists,
-    /// and moves the binary at the specified path to the installation
-    /// directory. It then ensures that the binary is executable.
-    pub fn install(&self, temp_bin: PathBuf) -> Result<PathBuf> {
-        // Ensure the installation directory exists
-        create_dir_all(&self.install_dir).context("Could not create the installation directory")?;
+    /// This functions ensures that the installation directory exists, makes
+    /// the binary at the specified path executable, and moves it into the
+    /// installation directory.
+    ///
+    /// The binary is swapped in with an atomic rename, so that a partially
+    /// written binary can never appear on the PATH. When the binary lives on
+    /// another filesystem, it is first copied next to its destination.
+    ///
+    /// Once in place, the new binary is run as a smoke test: if it fails to
+    /// execute or does not report the expected version, the previous binary
+    /// is restored. Otherwise, the previous binary replaces the backup, at the
+    /// path given by [Self::backup].
+    pub fn install(&self, temp_bin: PathBuf, version: &Version) -> Result<PathBuf> {
+        self.prepare()?;
 
         // Build the destination path
-        let bin = self
-            .install_dir
-            .join(format!("biome{}", self.platform.extension));
+        let bin = self.destination();
+
+        // Make the binary executable before it is moved into place
+        self.make_executable(&temp_bin)
+            .context("Failed to make the binary executable")?;
+
+        // Keep a copy of the previous binary, so that it can be restored
+        // later. The copy only replaces the backup once the new binary has
+        // been verified, so that a failed upgrade keeps the previous backup.
+        let previous = match bin.exists() {
+            true => Some(
+                self.stage_copy(&bin)
+                    .context("Failed to back up the previous binary")?,
+            ),
+            false => None,
+        };
 
         // Move the binary to the installation directory
-        rename(&temp_bin, &bin)
-            .context("Failed to move the binary to the installation directory")?;
+        match rename(&temp_bin, &bin) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::CrossesDevices => self
+                .stage_copy(&temp_bin)
+                .and_then(|staged| self.persist(staged, &bin))
+                .and_then(|_| Ok(remove_file(&temp_bin)?))
+                .context("Failed to copy the binary to the installation directory")?,
+            Err(err) => {
+                let _ = remove_file(&temp_bin);
 
-        // Make the binary executable
-        self.make_executable(&bin)
-            .context("Failed to make the binary executable")?;
+                return Err(io_error(&bin)(err))
+                    .context("Failed to move the binary to the installation directory");
+            }
+        }
+
+        // Make sure the new binary works, or put the previous one back
+        if let Err(err) = self.verify(&bin, version) {
+            match &previous {
+                Some(previous) => copy(previous.path(), &bin)
+                    .map(|_| ())
+                    .map_err(io_error(&bin))
+                    .context("Failed to restore the previous binary")?,
+                None => remove_file(&bin)
+                    .map_err(io_error(&bin))
+                    .context("Failed to remove the broken binary")?,
+            }
+
+            return Err(err);
+        }
+
+        if let Some(previous) = previous {
+            self.prepare_backup_dir()
+                .and_then(|_| self.persist(previous, &self.backup()))
+                .context("Failed to back up the previous binary")?;
+        }
 
         Ok(bin)
     }
 
-    /// Prepends the installation directory to the PATH environment variable
+    /// Restores the binary that was installed before the last upgrade
     ///
-    /// This function checks if the installation directory is already in the PATH,
-    /// and if not, it prepends it to the PATH using a platform specific method.
+    /// The current binary and the backup are swapped, so that rolling back
+    /// twice returns to the most recent binary. It returns the version
+    /// reported by the restored binary.
+    pub fn rollback(&self) -> Result<String> {
+        let bin = self.destination();
+        let backup = self.backup();
+
+        if !backup.exists() {
+            bail!("There is no previous version of Biome to roll back to");
+        }
+
+        let version = self
+            .installed_version(&backup)
+            .context("The previous binary does not work")?;
+
+        let current = match bin.exists() {
+            true => Some(
+                self.stage_copy(&bin)
+                    .context("Failed to back up the current binary")?,
+            ),
+            false => None,
+        };
+
+        rename(&backup, &bin)
+            .map_err(io_error(&bin))
+            .context("Failed to restore the previous binary")?;
+
+        if let Some(current) = current {
+            self.persist(current, &backup)
+                .context("Failed to back up the current binary")?;
+        }
+
+        Ok(version)
+    }
+
+    /// Returns the path at which the previous binary is kept
+    ///
+    /// The backup lives in a subdirectory, so that it does not end up on the
+    /// PATH along with the installation directory.
+    pub fn backup(&self) -> PathBuf {
+        self.install_dir
+            .join(BACKUP_DIR)
+            .join(format!("biome{}", self.platform.extension))
+    }
+
+    /// Ensures that the directory holding the backup exists
+    fn prepare_backup_dir(&self) -> Result<()> {
+        let dir = self.install_dir.join(BACKUP_DIR);
+
+        create_dir_all(&dir)
+            .map_err(io_error(&dir))
+            .context("Could not create the backup directory")
+    }
+
+    /// Checks that the binary runs and reports the expected version
+    fn verify(&self, bin: &Path, version: &Version) -> Result<()> {
+        let reported = self.installed_version(bin)?;
+
+        if reported != version.to_string() {
+            return Err(InstallerError::BrokenBinary {
+                path: bin.to_path_buf(),
+                reason: format!("it reports version {reported} instead of {version}"),
+                unloadable: false,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Runs the binary with `--version` and returns the version it reports
+    fn installed_version(&self, bin: &Path) -> Result<String> {
+        let broken = |reason: String, unloadable: bool| InstallerError::BrokenBinary {
+            path: bin.to_path_buf(),
+            reason,
+            unloadable,
+        };
+
+        // The system cannot execute a binary built for another architecture
+        // (ENOEXEC), or whose dynamic loader is missing (ENOENT), as is the
+        // case when it was built for another libc
+        let output = Command::new(bin).arg("--version").output().map_err(|err| {
+            let unloadable = err.kind() == ErrorKind::NotFound
+                || (cfg!(unix) && err.raw_os_error() == Some(ENOEXEC));
+
+            broken(format!("it could not be executed ({err})"), unloadable)
+        })?;
+
+        // The dynamic loader exits with 127 when a library is missing
+        if !output.status.success() {
+            return Err(broken(
+                format!("`biome --version` exited with {}", output.status),
+                output.status.code() == Some(127),
+            )
+            .into());
+        }
+
+        // Biome prints its version as `Version: X.Y.Z`
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        match stdout.split_whitespace().last() {
+            Some(version) => Ok(version.to_string()),
+            None => Err(broken("`biome --version` printed nothing".to_string(), false).into()),
+        }
+    }
+
+    /// Copies a binary to a temporary file in the installation directory
+    fn stage_copy(&self, source: &Path) -> Result<NamedTempFile> {
+        let mut staged = tempfile::Builder::new()
+            .prefix(".biome-")
+            .tempfile_in(&self.install_dir)
+            .map_err(io_error(&self.install_dir))?;
+
+        std::io::copy(&mut File::open(source)?, staged.as_file_mut())?;
+        staged.as_file().sync_all()?;
+
+        self.make_executable(staged.path())?;
+
+        Ok(staged)
+    }
+
+    /// Atomically moves a temporary file to the specified destination
+    fn persist(&self, staged: NamedTempFile, destination: &Path) -> Result<()> {
+        staged
+            .persist(destination)
+            .map_err(|err| io_error(destination)(err.error))?;
+
+        Ok(())
+    }
+
+    /// Returns the path at which the Biome binary is installed
+    pub fn destination(&self) -> PathBuf {
+        self.install_dir
+            .join(format!("biome{}", self.platform.extension))
+    }
+
+    /// Prepends the installation directory to the PATH environment variable
     ///
-    /// On Unix-like systems, it adds a line to the user's shell configuration file
-    /// (e.g., `.bashrc`, `.zshrc`) to export the PATH with the installation directory.
+    /// On Unix-like systems, it writes a block exporting the PATH with the
+    /// installation directory to the configuration file of the specified
+    /// shell (e.g., `.bashrc`, `.zshrc`), or replaces the block written by a
+    /// previous run. See [shell_config::write_path_block] for details.
     ///
     /// On Windows, it runs a PowerShell command to update the PATH environment variable
-    /// for the current user in a persistent way.
-    pub fn prepend_install_dir_to_path(&self) -> Result<UpdateType, PathmanError> {
-        prepend_to_path(&self.install_dir, Some("Biome installation dir"))
+    /// for the current user in a persistent way, and the shell is ignored.
+    ///
+    /// Any failure is reported as [InstallerError::PathUpdateFailed].
+    pub fn prepend_install_dir_to_path(&self, shell: Option<Shell>) -> Result<UpdateType> {
+        #[cfg(unix)]
+        let update = || -> Result<UpdateType> {
+            let shell = shell.context("Could not detect the shell")?;
+
+            let file = shell_config::path_file(shell)?
+                .with_context(|| format!("Could not find the configuration file of {shell}"))?;
+
+            shell_config::write_path_block(shell, &file, &self.install_dir)
+        };
+
+        #[cfg(windows)]
+        let update = || -> Result<UpdateType> {
+            Ok(pathman::prepend_to_path(
+                &self.install_dir,
+                Some(shell_config::PATH_COMMENT),
+            )?)
+        };
+
+        update().map_err(|err| InstallerError::PathUpdateFailed(err).into())
+    }
+
+    /// Adds the installation directory to the PATH of every user
+    ///
+    /// The directory is written to [system_profile], which is read by login
+    /// shells, instead of the configuration file of the current user.
+    pub fn prepend_install_dir_to_system_path(&self) -> Result<UpdateType> {
+        let change = self.plan_system_path_update();
+
+        if change.already_present {
+            return Ok(UpdateType::AlreadyInPath);
+        }
+
+        let profile = system_profile();
+
+        let content = match OS {
+            "macos" => format!("{}\n", change.line),
+            _ => format!("# Added by biome-installer\n{}\n", change.line),
+        };
+
+        if let Some(dir) = profile.parent() {
+            create_dir_all(dir).map_err(io_error(dir))?;
+        }
+
+        write(&profile, content)
+            .map_err(io_error(&profile))
+            .context("Could not write the system-wide profile")?;
+
+        Ok(UpdateType::Success)
+    }
+
+    /// Describes the change [Self::prepend_install_dir_to_system_path] would
+    /// make
+    pub fn plan_system_path_update(&self) -> PathChange {
+        let profile = system_profile();
+
+        let line = match OS {
+            "macos" => self.install_dir.display().to_string(),
+            _ => format!("export PATH=\"{}:$PATH\"", self.install_dir.display()),
+        };
+
+        let already_present = read_to_string(&profile)
+            .map(|content| content.lines().any(|existing| existing == line))
+            .unwrap_or(false);
+
+        PathChange {
+            target: profile.display().to_string(),
+            line,
+            already_present,
+            skipped_by: None,
+        }
+    }
+
+    /// Links the installed binary from the specified directory
+    ///
+    /// A previous link is replaced atomically, but a file that is not a link
+    /// is never overwritten. It returns the path of the link.
+    pub fn link(&self, dir: &Path) -> Result<PathBuf> {
+        let link = dir.join(format!("biome{}", self.platform.extension));
+
+        if let Ok(metadata) = link.symlink_metadata()
+            && !metadata.file_type().is_symlink()
+        {
+            bail!(
+                "{} already exists and is not a link, remove it to link Biome from {}",
+                link.display(),
+                dir.display()
+            );
+        }
+
+        #[cfg(unix)]
+        {
+            let staged = dir.join(format!(".biome-{}.link", std::process::id()));
+
+            std::os::unix::fs::symlink(self.destination(), &staged)
+                .and_then(|_| rename(&staged, &link))
+                .map_err(io_error(&link))
+                .with_context(|| format!("Could not link Biome from {}", dir.display()))?;
+
+            Ok(link)
+        }
+
+        #[cfg(windows)]
+        {
+            bail!("Linking Biome from another directory is not supported on Windows")
+        }
+    }
+
+    /// Describes the change [Self::prepend_install_dir_to_path] would make
+    ///
+    /// Nothing is written: on Unix-like systems, the export line is written to
+    /// the configuration file of the specified shell picked by
+    /// [shell_config::path_file], and on Windows, the directory is prepended to
+    /// the user's PATH in the registry.
+    ///
+    /// It returns `None` if the PATH could not be updated.
+    pub fn plan_path_update(&self, shell: Option<Shell>) -> Option<PathChange> {
+        #[cfg(unix)]
+        {
+            let shell = shell?;
+            let file = shell_config::path_file(shell).ok()??;
+            let line = shell.prepend_command(&self.install_dir);
+
+            let already_present =
+                shell_config::has_path_entry(shell, &file, &self.install_dir).unwrap_or(false);
+
+            let skipped_by = shell_config::login_file_skipping(shell, &file)
+                .map(|login| login.display().to_string());
+
+            Some(PathChange {
+                target: file.display().to_string(),
+                line,
+                already_present,
+                skipped_by,
+            })
+        }
+
+        #[cfg(windows)]
+        {
+            use winreg::{RegKey, enums::HKEY_CURRENT_USER};
+
+            let _ = shell;
+
+            let path: String = RegKey::predef(HKEY_CURRENT_USER)
+                .open_subkey("Environment")
+                .and_then(|key| key.get_value("Path"))
+                .unwrap_or_default();
+
+            let line = self.install_dir.display().to_string();
+
+            let already_present = path.split(';').any(|entry| {
+                entry.trim_end_matches(['/', '\\']) == line.trim_end_matches(['/', '\\'])
+            });
+
+            Some(PathChange {
+                target: r"HKEY_CURRENT_USER\Environment\Path".to_string(),
+                line,
+                already_present,
+                skipped_by: None,
+            })
+        }
     }
 
     /// Makes the binary executable
@@ -65,18 +591,20 @@ impl Installer {
     /// This function sets the executable permissions on the binary file.
     /// On Unix-like systems, it sets the permissions to `755`.
     /// On Windows, this is a no-op since .exe files are executable by default.
-    fn make_executable(&self, bin: &PathBuf) -> Result<()> {
+    fn make_executable(&self, bin: &Path) -> Result<()> {
         #[cfg(unix)]
         {
             use std::os::unix::fs::PermissionsExt;
 
             let mut perms = std::fs::metadata(bin)
+                .map_err(io_error(bin))
                 .context("Failed to get metadata for the binary")?
                 .permissions();
 
             perms.set_mode(0o755); // Set executable permissions
 
             std::fs::set_permissions(bin, perms)
+                .map_err(io_error(bin))
                 .context("Failed to set permissions for the binary")?;
         }
         Ok(())
@@ -1,41 +1,94 @@
 use anyhow::Result;
 use clap::Parser;
-use colored::Colorize;
 use std::process;
 
-use crate::commands::Commands;
+use crate::{
+    commands::Commands,
+    config::Config,
+    error::{EXIT_CODES, InstallerError},
+    output::{Output, OutputFormat},
+};
 
+mod bundle;
+mod cache;
 mod commands;
+mod config;
 mod downloader;
+mod error;
+mod http;
 mod installer;
+mod npm;
+mod output;
 mod platform;
+mod project;
+mod shell_config;
+mod versions;
 
 #[derive(Parser)]
 #[command(name = "biome-installer")]
 #[command(about = "A cross-platform installer for Biome")]
 #[command(version = "0.1.0")]
+#[command(after_long_help = EXIT_CODES)]
 struct Cli {
     #[command(subcommand)]
     command: commands::Commands,
+
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        env = "BIOME_OUTPUT",
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        global = true,
+        help = "The format in which to report results and errors",
+    )]
+    output: OutputFormat,
 }
 
 fn main() {
-    if let Err(err) = run() {
-        eprintln!("{} {}", "✘".red(), err.to_string().red());
+    let cli = Cli::try_parse().unwrap_or_else(|err| exit_with_parse_error(err));
+    let output = Output::new(cli.output);
 
-        for cause in err.chain().skip(1) {
-            eprintln!("{} {}", "→ caused by:".dimmed(), cause.to_string().dimmed());
-        }
+    if let Err(err) = run(cli.command, &output) {
+        output.error(&err);
 
-        process::exit(1);
+        process::exit(InstallerError::find(&err).map_or(1, InstallerError::exit_code));
     }
 }
 
-fn run() -> Result<()> {
-    let cli = Cli::parse();
+/// Reports invalid command-line arguments and exits
+///
+/// Since the arguments could not be parsed, the output format is looked up
+/// in the raw arguments and the environment. Help and version requests, which
+/// clap reports as errors, are printed as usual.
+fn exit_with_parse_error(err: clap::Error) -> ! {
+    let args: Vec<String> = std::env::args().collect();
+
+    let json = args
+        .windows(2)
+        .any(|pair| pair[0] == "--output" && pair[1] == "json")
+        || args.iter().any(|arg| arg == "--output=json")
+        || std::env::var("BIOME_OUTPUT").is_ok_and(|format| format == "json");
+
+    if !json || !err.use_stderr() {
+        err.exit();
+    }
+
+    let err = InstallerError::InvalidArguments(err);
+
+    Output::new(OutputFormat::Json).error(&err.into());
+
+    process::exit(2);
+}
 
-    match cli.command {
-        Commands::Install(cmd) => cmd.handle()?,
+fn run(command: Commands, output: &Output) -> Result<()> {
+    match command {
+        Commands::Install(cmd) => cmd.with_config(Config::load()?).handle(output)?,
+        Commands::Rollback(cmd) => cmd.with_config(Config::load()?).handle(output)?,
+        Commands::Cache(cmd) => cmd.handle(output)?,
+        Commands::Bundle(cmd) => cmd.with_config(Config::load()?).handle(output)?,
+        Commands::Config(cmd) => cmd.handle(output)?,
+        Commands::Path(cmd) => cmd.handle(output)?,
     }
 
     Ok(())
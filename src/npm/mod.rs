@@ -0,0 +1,222 @@
+use std::{
+    collections::HashMap,
+    io::{Write, copy},
+};
+
+use anyhow::{Context, Result, bail};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use flate2::read::GzDecoder;
+use semver::Version;
+use serde::Deserialize;
+use sha2::{Digest, Sha512};
+
+use crate::{
+    error::InstallerError,
+    http::Http,
+    versions::{VersionList, VersionProvider},
+};
+
+/// The URL of the public npm registry
+pub const DEFAULT_NPM_REGISTRY: &str = "https://registry.npmjs.org";
+
+/// The npm package of Biome, whose versions are those of the binaries
+const MAIN_PACKAGE: &str = "@biomejs/biome";
+
+/// An npm registry serving the packages of Biome
+///
+/// Biome publishes its binaries as per-platform packages, such as
+/// `@biomejs/cli-linux-x64`, each holding a single binary.
+#[derive(Clone)]
+pub struct NpmRegistry {
+    http: Http,
+    url: String,
+}
+
+/// The metadata of a package, as returned by the registry
+#[derive(Deserialize)]
+struct Packument {
+    #[serde(rename = "dist-tags", default)]
+    dist_tags: HashMap<String, String>,
+    #[serde(default)]
+    versions: HashMap<String, PackageVersion>,
+}
+
+/// The metadata of a version of a package
+#[derive(Deserialize)]
+struct PackageVersion {
+    dist: Dist,
+}
+
+/// Where to download a version of a package, and how to verify it
+#[derive(Deserialize)]
+struct Dist {
+    tarball: String,
+    integrity: Option<String>,
+}
+
+impl NpmRegistry {
+    /// Creates a client for the specified registry, or the public one
+    pub fn new(http: Http, url: Option<&str>) -> Self {
+        NpmRegistry {
+            http,
+            url: url
+                .unwrap_or(DEFAULT_NPM_REGISTRY)
+                .trim_end_matches('/')
+                .to_string(),
+        }
+    }
+
+    /// Computes the conventional URL of the tarball of a version of a package
+    ///
+    /// The registry may serve the tarball from another URL, which is read from
+    /// the metadata of the package when downloading it.
+    pub fn tarball_url(&self, package: &str, version: &Version) -> String {
+        let name = package.rsplit('/').next().unwrap_or(package);
+
+        format!("{}/{package}/-/{name}-{version}.tgz", self.url)
+    }
+
+    /// Downloads the specified binary of a version of a package
+    ///
+    /// The integrity of the tarball is verified against the metadata of the
+    /// package before the binary is extracted from it into `writer`.
+    pub fn download(
+        &self,
+        package: &str,
+        version: &Version,
+        binary: &str,
+        writer: &mut impl Write,
+    ) -> Result<()> {
+        let dist = self
+            .packument(package)?
+            .and_then(|mut packument| packument.versions.remove(&version.to_string()))
+            .ok_or_else(|| InstallerError::VersionNotFound(version.clone()))?
+            .dist;
+
+        let integrity = dist
+            .integrity
+            .with_context(|| format!("{package}@{version} has no integrity hash"))?;
+
+        let Some(tarball) = self.http.bytes(&dist.tarball)? else {
+            return Err(InstallerError::VersionNotFound(version.clone()).into());
+        };
+
+        verify_integrity(&dist.tarball, &tarball, &integrity)?;
+
+        let path = format!("package/{binary}");
+        let mut archive = tar::Archive::new(GzDecoder::new(tarball.as_slice()));
+
+        for entry in archive
+            .entries()
+            .context("Could not read the package tarball")?
+        {
+            let mut entry = entry.context("Could not read the package tarball")?;
+
+            if entry.path()?.to_string_lossy() == path {
+                copy(&mut entry, writer).context("Could not extract the binary")?;
+
+                return Ok(());
+            }
+        }
+
+        bail!("{package}@{version} does not contain {binary}")
+    }
+
+    /// Checks whether the registry holds a version of a package
+    pub fn has_version(&self, package: &str, version: &Version) -> Result<bool> {
+        Ok(self
+            .packument(package)?
+            .is_some_and(|packument| packument.versions.contains_key(&version.to_string())))
+    }
+
+    /// Fetches the metadata of a package
+    ///
+    /// It returns `None` if the package does not exist in the registry.
+    fn packument(&self, package: &str) -> Result<Option<Packument>> {
+        let url = format!("{}/{}", self.url, package.replace('/', "%2f"));
+
+        let Some(content) = self.http.bytes(&url)? else {
+            return Ok(None);
+        };
+
+        let packument = serde_json::from_slice(&content)
+            .with_context(|| format!("Invalid package metadata at {url}"))?;
+
+        Ok(Some(packument))
+    }
+}
+
+impl VersionProvider for NpmRegistry {
+    fn name(&self) -> &'static str {
+        "the npm registry"
+    }
+
+    fn fetch(&self, list: VersionList, _until: Option<&Version>) -> Result<Vec<Version>> {
+        let packument = self
+            .packument(MAIN_PACKAGE)?
+            .with_context(|| format!("{MAIN_PACKAGE} could not be found in {}", self.url))?;
+
+        if list == VersionList::Latest {
+            let latest = packument
+                .dist_tags
+                .get("latest")
+                .context("The package has no latest version")?;
+
+            return Ok(vec![Version::parse(latest)?]);
+        }
+
+        let mut versions: Vec<Version> = packument
+            .versions
+            .keys()
+            .filter_map(|version| Version::parse(version).ok())
+            .filter(|version| list == VersionList::Preview || version.pre.is_empty())
+            .collect();
+
+        versions.sort_by(|a, b| b.cmp(a));
+
+        Ok(versions)
+    }
+}
+
+/// Computes the name of the npm package holding the specified asset
+///
+/// The packages are named after the assets published on GitHub, e.g.
+/// `biome-linux-x64-musl` is published as `@biomejs/cli-linux-x64-musl`.
+pub fn package_name(asset: &str) -> String {
+    let platform = asset.trim_start_matches("biome-").trim_end_matches(".exe");
+
+    format!("@biomejs/cli-{platform}")
+}
+
+/// Computes the name of the binary in the npm package holding the specified
+/// asset
+pub fn binary_name(asset: &str) -> &'static str {
+    match asset.ends_with(".exe") {
+        true => "biome.exe",
+        false => "biome",
+    }
+}
+
+/// Verifies the content of a file against a Subresource Integrity string
+///
+/// Only SHA-512 hashes are supported, which is what npm has been using for
+/// every package published in recent years.
+fn verify_integrity(file: &str, content: &[u8], integrity: &str) -> Result<()> {
+    let expected = integrity
+        .split_whitespace()
+        .find_map(|hash| hash.strip_prefix("sha512-"))
+        .with_context(|| format!("Unsupported integrity hash for {file}: {integrity}"))?;
+
+    let actual = STANDARD.encode(Sha512::digest(content));
+
+    if actual != expected {
+        return Err(InstallerError::ChecksumMismatch {
+            file: file.to_string(),
+            expected: format!("sha512-{expected}"),
+            actual: format!("sha512-{actual}"),
+        }
+        .into());
+    }
+
+    Ok(())
+}
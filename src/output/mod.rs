@@ -0,0 +1,166 @@
+use std::{cell::RefCell, fmt::Display};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use colored::Colorize;
+use serde::Serialize;
+use spinners::{Spinner, Spinners};
+
+use crate::error::InstallerError;
+
+/// The format in which commands report their results
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable, coloured text
+    #[default]
+    Text,
+
+    /// A single JSON document on stdout, and JSON errors on stderr
+    Json,
+}
+
+/// Command output
+///
+/// Commands report their progress and results through this type, so that the
+/// same code path can either print human-readable text or emit a single
+/// structured JSON document.
+pub struct Output {
+    format: OutputFormat,
+    warnings: RefCell<Vec<String>>,
+}
+
+/// A JSON document emitted by a command
+#[derive(Serialize)]
+struct Document<'a, T: Serialize> {
+    #[serde(flatten)]
+    report: &'a T,
+    warnings: Vec<String>,
+}
+
+/// A JSON error emitted on stderr
+#[derive(Serialize)]
+struct ErrorDocument<'a> {
+    error: ErrorReport<'a>,
+}
+
+#[derive(Serialize)]
+struct ErrorReport<'a> {
+    code: &'a str,
+    message: String,
+    causes: Vec<String>,
+}
+
+impl Output {
+    /// Creates a new output for the given format
+    pub fn new(format: OutputFormat) -> Self {
+        Output {
+            format,
+            warnings: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Whether the output is meant to be consumed by a machine
+    pub fn is_json(&self) -> bool {
+        self.format == OutputFormat::Json
+    }
+
+    /// Prints a line of human-readable text
+    ///
+    /// This is a no-op in JSON mode, where only the final document is printed.
+    pub fn print(&self, message: impl Display) {
+        if !self.is_json() {
+            println!("{message}");
+        }
+    }
+
+    /// Reports a warning
+    ///
+    /// Warnings are printed right away in text mode, and collected in the
+    /// `warnings` field of the final document in JSON mode.
+    pub fn warn(&self, message: impl Into<String>) {
+        let message = message.into();
+
+        match self.format {
+            OutputFormat::Text => println!("{}", format!("⚠ {message}").yellow()),
+            OutputFormat::Json => self.warnings.borrow_mut().push(message),
+        }
+    }
+
+    /// Starts a spinner for a long-running operation
+    ///
+    /// No spinner is displayed in JSON mode.
+    pub fn spinner(&self, message: impl Into<String>) -> Progress {
+        Progress {
+            spinner: match self.format {
+                OutputFormat::Text => Some(Spinner::new(Spinners::Dots, message.into())),
+                OutputFormat::Json => None,
+            },
+        }
+    }
+
+    /// Emits the final report of a command
+    ///
+    /// This is a no-op in text mode, where the results have already been
+    /// printed as the command progressed.
+    pub fn report<T: Serialize>(&self, report: &T) -> Result<()> {
+        if self.is_json() {
+            let document = Document {
+                report,
+                warnings: self.warnings.take(),
+            };
+
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&document)
+                    .context("Failed to serialize the report")?
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Reports an error that caused a command to fail
+    pub fn error(&self, err: &anyhow::Error) {
+        match self.format {
+            OutputFormat::Text => {
+                eprintln!("{} {}", "✘".red(), err.to_string().red());
+
+                for cause in err.chain().skip(1) {
+                    eprintln!("{} {}", "→ caused by:".dimmed(), cause.to_string().dimmed());
+                }
+            }
+            OutputFormat::Json => {
+                let document = ErrorDocument {
+                    error: ErrorReport {
+                        code: InstallerError::find(err)
+                            .map_or("internal_error", InstallerError::code),
+                        message: err.to_string(),
+                        causes: err.chain().skip(1).map(|cause| cause.to_string()).collect(),
+                    },
+                };
+
+                match serde_json::to_string(&document) {
+                    Ok(json) => eprintln!("{json}"),
+                    Err(_) => eprintln!("{err}"),
+                }
+            }
+        }
+    }
+}
+
+/// A spinner displayed while a long-running operation is in progress
+pub struct Progress {
+    spinner: Option<Spinner>,
+}
+
+impl Progress {
+    /// Stops the spinner and replaces it with a success message
+    pub fn success(mut self, message: impl Display) {
+        if let Some(spinner) = self.spinner.as_mut() {
+            spinner.stop_and_persist(
+                &"✔".green().to_string(),
+                message.to_string().green().to_string(),
+            );
+        }
+    }
+}
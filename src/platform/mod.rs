@@ -1,13 +1,17 @@
 use std::{
-    env::consts::{ARCH, OS},
+    env::consts::{ARCH, EXE_SUFFIX, OS},
     fmt::Display,
-    path::PathBuf,
+    fs::read_to_string,
+    path::{Path, PathBuf},
     process::Command,
 };
 
 use anyhow::{Context, Result};
+use clap::ValueEnum;
 use home::home_dir;
+use serde::Serialize;
 
+#[derive(Serialize)]
 pub struct Platform {
     pub os: String,
     pub arch: String,
@@ -16,11 +20,23 @@ pub struct Platform {
     pub shell: Option<Shell>,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
 pub enum Libc {
     Musl,
     Glibc,
 }
 
+impl Libc {
+    /// Returns the other libc implementation
+    pub fn other(&self) -> Libc {
+        match self {
+            Libc::Musl => Libc::Glibc,
+            Libc::Glibc => Libc::Musl,
+        }
+    }
+}
+
 impl Display for Libc {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -30,27 +46,218 @@ impl Display for Libc {
     }
 }
 
+impl Display for Platform {
+    /// Describes the platform as Biome builds target it, e.g. `linux aarch64
+    /// musl` or `macos x86_64`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.os.as_str() {
+            "linux" => write!(f, "{} {} {}", self.os, self.arch, self.libc),
+            _ => write!(f, "{} {}", self.os, self.arch),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
 pub enum Shell {
     Bash,
     Zsh,
     Fish,
+    #[value(alias = "nu")]
+    Nushell,
+    Elvish,
+    Xonsh,
+    #[value(alias = "csh")]
+    Tcsh,
+    #[value(alias = "pwsh")]
+    Powershell,
+}
+
+impl Display for Shell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Shell::Bash => write!(f, "bash"),
+            Shell::Zsh => write!(f, "zsh"),
+            Shell::Fish => write!(f, "fish"),
+            Shell::Nushell => write!(f, "nushell"),
+            Shell::Elvish => write!(f, "elvish"),
+            Shell::Xonsh => write!(f, "xonsh"),
+            Shell::Tcsh => write!(f, "tcsh"),
+            Shell::Powershell => write!(f, "powershell"),
+        }
+    }
 }
 
 impl Shell {
-    pub fn config_file(&self) -> Result<PathBuf> {
-        let home_dir = home_dir().context("Could not determine the home directory")?;
+    /// Finds the shell run by the specified program, e.g. `/bin/zsh` or
+    /// `/usr/bin/pwsh`
+    pub fn from_program(program: &Path) -> Option<Shell> {
+        let name = program.file_name()?.to_str()?;
 
-        let config_file = match self {
+        match name {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            "nu" => Some(Shell::Nushell),
+            "elvish" => Some(Shell::Elvish),
+            "xonsh" => Some(Shell::Xonsh),
+            "tcsh" | "csh" => Some(Shell::Tcsh),
+            "pwsh" => Some(Shell::Powershell),
+            _ => None,
+        }
+    }
+
+    /// Returns the names of the programs that run the shell
+    fn programs(&self) -> &'static [&'static str] {
+        match self {
+            Shell::Bash => &["bash"],
+            Shell::Zsh => &["zsh"],
+            Shell::Fish => &["fish"],
+            Shell::Nushell => &["nu"],
+            Shell::Elvish => &["elvish"],
+            Shell::Xonsh => &["xonsh"],
+            Shell::Tcsh => &["tcsh", "csh"],
+            Shell::Powershell => &["pwsh"],
+        }
+    }
+
+    /// Checks whether the shell is installed
+    ///
+    /// The shell is installed if it is listed in [SHELLS_FILE], as login
+    /// shells are, or if one of its programs is found in the PATH.
+    pub fn is_installed(&self) -> bool {
+        let listed = read_to_string(SHELLS_FILE).is_ok_and(|shells| {
+            shells
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.starts_with('#'))
+                .any(|line| Shell::from_program(Path::new(line)) == Some(*self))
+        });
+
+        listed
+            || std::env::var_os("PATH").is_some_and(|path| {
+                std::env::split_paths(&path).any(|dir| {
+                    self.programs()
+                        .iter()
+                        .any(|program| dir.join(format!("{program}{EXE_SUFFIX}")).is_file())
+                })
+            })
+    }
+
+    /// Returns the configuration files of the shell the PATH may be updated
+    /// in, in the order in which the first existing one is picked
+    ///
+    /// The files are resolved like the shells do: `ZDOTDIR` replaces the home
+    /// directory for zsh, and `XDG_CONFIG_HOME` replaces `~/.config` for the
+    /// shells that follow the XDG Base Directory specification.
+    pub fn config_files(&self) -> Result<Vec<PathBuf>> {
+        let home = home_dir().context("Could not determine the home directory")?;
+        let config_home = xdg_config_home().unwrap_or_else(|| home.join(".config"));
+
+        let files = match self {
             Shell::Bash => match OS {
-                "macos" => home_dir.join(".bash_profile"),
-                _ => home_dir.join(".bashrc"),
+                // Terminals start login shells on macOS, which only read
+                // .bash_profile
+                "macos" => vec![
+                    home.join(".bash_profile"),
+                    home.join(".bashrc"),
+                    home.join(".profile"),
+                ],
+                _ => vec![
+                    home.join(".bashrc"),
+                    home.join(".bash_profile"),
+                    home.join(".profile"),
+                ],
             },
-            Shell::Zsh => home_dir.join(".zshrc"),
-            Shell::Fish => home_dir.join(".config/fish/config.fish"),
+            Shell::Zsh => {
+                let dir = absolute_var("ZDOTDIR").unwrap_or_else(|| home.clone());
+
+                vec![dir.join(".zshrc")]
+            }
+            Shell::Fish => vec![config_home.join("fish/config.fish")],
+            Shell::Nushell => {
+                let dir = match (xdg_config_home(), OS) {
+                    (Some(dir), _) => dir.join("nushell"),
+                    (None, "macos") => home.join("Library/Application Support/nushell"),
+                    (None, _) => home.join(".config/nushell"),
+                };
+
+                vec![dir.join("env.nu"), dir.join("config.nu")]
+            }
+            Shell::Elvish => vec![
+                config_home.join("elvish/rc.elv"),
+                home.join(".elvish/rc.elv"),
+            ],
+            Shell::Xonsh => vec![home.join(".xonshrc"), config_home.join("xonsh/rc.xsh")],
+            Shell::Tcsh => vec![home.join(".tcshrc"), home.join(".cshrc")],
+            Shell::Powershell => {
+                vec![config_home.join("powershell/Microsoft.PowerShell_profile.ps1")]
+            }
         };
 
-        Ok(config_file)
+        Ok(files)
+    }
+
+    /// Builds the line that prepends the given directory to the PATH
+    pub fn prepend_command(&self, dir: &Path) -> String {
+        let dir = dir.display();
+
+        match self {
+            Shell::Bash | Shell::Zsh => format!("export PATH=\"{dir}:$PATH\""),
+            Shell::Fish => format!("set -gx PATH \"{dir}\" $PATH"),
+            Shell::Nushell => format!("$env.PATH = ($env.PATH | prepend \"{dir}\")"),
+            Shell::Elvish => format!("set paths = [\"{dir}\" $@paths]"),
+            Shell::Xonsh => format!("$PATH.insert(0, \"{dir}\")"),
+            Shell::Tcsh => format!("set path = (\"{dir}\" $path)"),
+            Shell::Powershell => format!("$env:PATH = \"{dir}:$env:PATH\""),
+        }
     }
+
+    /// Parses a line built by [Self::prepend_command], and returns the
+    /// directory it prepends to the PATH
+    pub fn parse_prepend_command(&self, line: &str) -> Option<PathBuf> {
+        let (prefix, suffix) = match self {
+            Shell::Bash | Shell::Zsh => ("export PATH=\"", ":$PATH\""),
+            Shell::Fish => ("set -gx PATH \"", "\" $PATH"),
+            Shell::Nushell => ("$env.PATH = ($env.PATH | prepend \"", "\")"),
+            Shell::Elvish => ("set paths = [\"", "\" $@paths]"),
+            Shell::Xonsh => ("$PATH.insert(0, \"", "\")"),
+            Shell::Tcsh => ("set path = (\"", "\" $path)"),
+            Shell::Powershell => ("$env:PATH = \"", ":$env:PATH\""),
+        };
+
+        let dir = line.strip_prefix(prefix)?.strip_suffix(suffix)?;
+
+        Some(PathBuf::from(dir))
+    }
+}
+
+/// The file listing the login shells installed on the system
+const SHELLS_FILE: &str = "/etc/shells";
+
+/// The programs that run the installer on behalf of the shell of the user
+#[cfg(target_os = "linux")]
+const SHELL_WRAPPERS: &[&str] = &[
+    "sh", "dash", "sudo", "doas", "su", "env", "nohup", "timeout",
+];
+
+/// How many parent processes are looked up to find the shell of the user
+#[cfg(target_os = "linux")]
+const MAX_PARENT_PROCESSES: usize = 8;
+
+/// Returns the value of an environment variable holding an absolute path
+///
+/// Relative paths are ignored, as the XDG Base Directory specification
+/// requires.
+fn absolute_var(name: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(std::env::var_os(name)?);
+
+    path.is_absolute().then_some(path)
+}
+
+/// Returns the directory set in `XDG_CONFIG_HOME`, if any
+fn xdg_config_home() -> Option<PathBuf> {
+    absolute_var("XDG_CONFIG_HOME")
 }
 
 impl Platform {
@@ -91,12 +298,57 @@ impl Platform {
         }
     }
 
+    /// Detects the shell the installer was run from
+    ///
+    /// On Linux, the parent processes are looked up first, since `SHELL` is
+    /// the login shell of the user, which may not be the one they are using,
+    /// and is often unset in containers. Otherwise, or if no shell is found
+    /// among the parent processes, the shell is read from `SHELL`.
     fn detect_shell() -> Option<Shell> {
-        match std::env::var_os("SHELL") {
-            Some(shell) if shell.to_string_lossy().contains("bash") => Some(Shell::Bash),
-            Some(shell) if shell.to_string_lossy().contains("zsh") => Some(Shell::Zsh),
-            Some(shell) if shell.to_string_lossy().contains("fish") => Some(Shell::Fish),
-            _ => None,
+        #[cfg(target_os = "linux")]
+        if let Some(shell) = Self::detect_parent_shell() {
+            return Some(shell);
         }
+
+        Shell::from_program(Path::new(&std::env::var_os("SHELL")?))
+    }
+
+    /// Walks up the parent processes to find the shell the installer was run
+    /// from
+    ///
+    /// The processes that merely run the installer on behalf of a shell, such
+    /// as `sudo` or the `sh` reading an installation script, are skipped, but
+    /// the walk stops at any other process, since the installer was then not
+    /// run from a shell.
+    #[cfg(target_os = "linux")]
+    fn detect_parent_shell() -> Option<Shell> {
+        let mut pid = std::os::unix::process::parent_id();
+
+        for _ in 0..MAX_PARENT_PROCESSES {
+            let process = PathBuf::from(format!("/proc/{pid}"));
+            let comm = std::fs::read_to_string(process.join("comm")).ok()?;
+            let name = comm.trim().trim_start_matches('-');
+
+            if !SHELL_WRAPPERS.contains(&name) {
+                // The name is truncated to 15 characters, so the executable
+                // is looked up as well
+                return Shell::from_program(Path::new(name)).or_else(|| {
+                    Shell::from_program(&std::fs::read_link(process.join("exe")).ok()?)
+                });
+            }
+
+            // The parent is the second field after the name, which is
+            // enclosed in parentheses and may contain spaces
+            let stat = std::fs::read_to_string(process.join("stat")).ok()?;
+            pid = stat
+                .rsplit_once(')')?
+                .1
+                .split_whitespace()
+                .nth(1)?
+                .parse()
+                .ok()?;
+        }
+
+        None
     }
 }
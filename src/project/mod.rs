@@ -0,0 +1,87 @@
+use std::{
+    fs::{read_to_string, write},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use semver::Version;
+
+use crate::error::io_error;
+
+/// The directory, relative to the project root, in which Biome is installed
+/// by default with `--local`
+pub const DEFAULT_LOCAL_DIR: &str = ".biome/bin";
+
+/// The file, at the project root, recording the version of Biome installed
+/// into the project
+pub const VERSION_FILE: &str = ".biome-version";
+
+/// The files that mark the root of a project
+const ROOT_MARKERS: &[&str] = &["biome.json", "biome.jsonc", "package.json"];
+
+/// A project in which Biome is installed locally
+pub struct Project {
+    root: PathBuf,
+}
+
+impl Project {
+    /// Finds the project the specified directory belongs to
+    ///
+    /// The root of the project is the closest directory, starting from the
+    /// specified one and walking up, that holds a Biome configuration file or
+    /// a `package.json`.
+    pub fn find(dir: &Path) -> Result<Self> {
+        for ancestor in dir.ancestors() {
+            if ROOT_MARKERS
+                .iter()
+                .any(|marker| ancestor.join(marker).is_file())
+            {
+                return Ok(Project {
+                    root: ancestor.to_path_buf(),
+                });
+            }
+        }
+
+        bail!(
+            "Could not find the root of the project: neither {} nor any of its parents contains \
+             biome.json or package.json",
+            dir.display()
+        )
+    }
+
+    /// Returns the root directory of the project
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the version of Biome recorded for the project, if any
+    pub fn version(&self) -> Result<Option<Version>> {
+        let path = self.version_file();
+
+        let content = match read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(io_error(&path)(err)),
+        };
+
+        let version = Version::parse(content.trim())
+            .with_context(|| format!("Invalid version in {}", path.display()))?;
+
+        Ok(Some(version))
+    }
+
+    /// Records the version of Biome installed into the project
+    pub fn record_version(&self, version: &Version) -> Result<()> {
+        let path = self.version_file();
+
+        write(&path, format!("{version}\n"))
+            .map_err(io_error(&path))
+            .context("Could not record the installed version")
+    }
+
+    /// Returns the path of the file recording the version of Biome
+    pub fn version_file(&self) -> PathBuf {
+        self.root.join(VERSION_FILE)
+    }
+}
@@ -0,0 +1,431 @@
+use std::{
+    fs::{copy, read_to_string, write},
+    io::ErrorKind,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use pathman::UpdateType;
+use serde::Serialize;
+
+use crate::{error::io_error, platform::Shell};
+
+/// The comment written above the lines that update the PATH by previous
+/// versions of the installer
+pub const PATH_COMMENT: &str = "Biome installation dir";
+
+/// The line opening the block managed by the installer
+pub const BLOCK_START: &str = "# >>> biome-installer >>>";
+
+/// The line closing the block managed by the installer
+pub const BLOCK_END: &str = "# <<< biome-installer <<<";
+
+/// The files bash reads when started as a login shell, of which only the
+/// first existing one is read
+const BASH_LOGIN_FILES: &[&str] = &[".bash_profile", ".bash_login", ".profile"];
+
+/// The lines removed from a shell configuration file
+#[derive(Serialize)]
+pub struct Removal {
+    /// The shell configuration file
+    pub file: PathBuf,
+
+    /// The copy of the file made before removing the lines
+    pub backup: PathBuf,
+
+    /// The lines that updated the PATH, without their comments
+    pub lines: Vec<String>,
+}
+
+/// Removes the lines that prepend a directory to the PATH from the
+/// configuration files of every shell
+///
+/// Only the blocks managed by the installer, and the lines written below
+/// [PATH_COMMENT] by previous versions, are removed, unless a directory is
+/// specified, in which case any line that prepends exactly this directory is
+/// removed as well. See [remove_path_entries_from] for details.
+pub fn remove_path_entries(dir: Option<&Path>) -> Result<Vec<Removal>> {
+    let mut removals = Vec::new();
+    let mut visited = Vec::new();
+
+    for shell in Shell::value_variants() {
+        for file in shell.config_files()? {
+            if visited.contains(&file) {
+                continue;
+            }
+
+            if let Some(removal) = remove_path_entries_from(*shell, &file, dir)? {
+                removals.push(removal);
+            }
+
+            visited.push(file);
+        }
+    }
+
+    Ok(removals)
+}
+
+/// Removes the lines that prepend a directory to the PATH from the
+/// specified configuration file of a shell
+///
+/// A block managed by the installer is removed as a whole, and the comment
+/// above a line written by previous versions is removed along with it. The
+/// empty line that was added before either of them is removed as well, while
+/// the rest of the file is kept byte for byte. The file is copied to `<file>.biome-installer.bak`
+/// before being modified.
+///
+/// It returns `None` if the file does not exist or holds no such line.
+pub fn remove_path_entries_from(
+    shell: Shell,
+    file: &Path,
+    dir: Option<&Path>,
+) -> Result<Option<Removal>> {
+    let content = match read_to_string(file) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(io_error(file)(err)),
+    };
+
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let legacy = legacy_entries(shell, &lines);
+    let mut removed = vec![false; lines.len()];
+    let mut entries = Vec::new();
+
+    for block in blocks(&lines) {
+        let block_entries: Vec<(String, PathBuf)> = lines[block.clone()]
+            .iter()
+            .filter_map(|line| {
+                let line = line.trim();
+                shell
+                    .parse_prepend_command(line)
+                    .map(|prepended| (line.to_string(), prepended))
+            })
+            .collect();
+
+        let matches = match dir {
+            Some(dir) => block_entries.iter().any(|(_, prepended)| prepended == dir),
+            None => true,
+        };
+
+        if !matches {
+            continue;
+        }
+
+        removed[block.clone()].fill(true);
+        entries.extend(block_entries.into_iter().map(|(line, _)| line));
+
+        if block.start > 0 && lines[block.start - 1].trim().is_empty() {
+            removed[block.start - 1] = true;
+        }
+    }
+
+    for (index, line) in lines.iter().enumerate() {
+        if removed[index] {
+            continue;
+        }
+
+        let line = line.trim();
+
+        let Some(prepended) = shell.parse_prepend_command(line) else {
+            continue;
+        };
+
+        let has_comment = legacy.contains(&index);
+
+        let matches = match dir {
+            Some(dir) => prepended == dir,
+            None => has_comment,
+        };
+
+        if !matches {
+            continue;
+        }
+
+        removed[index] = true;
+        entries.push(line.to_string());
+
+        if has_comment {
+            removed[index - 1] = true;
+
+            if index > 1 && lines[index - 2].trim().is_empty() {
+                removed[index - 2] = true;
+            }
+        }
+    }
+
+    if entries.is_empty() {
+        return Ok(None);
+    }
+
+    let backup = backup_path(file);
+
+    copy(file, &backup)
+        .map_err(io_error(&backup))
+        .context("Could not back up the shell configuration file")?;
+
+    let content: String = lines
+        .iter()
+        .zip(&removed)
+        .filter(|(_, removed)| !**removed)
+        .map(|(line, _)| *line)
+        .collect();
+
+    write(file, content)
+        .map_err(io_error(file))
+        .context("Could not update the shell configuration file")?;
+
+    Ok(Some(Removal {
+        file: file.to_path_buf(),
+        backup,
+        lines: entries,
+    }))
+}
+
+/// Returns the shells that are installed and have a configuration file, along
+/// with the file in which the PATH would be updated
+///
+/// This is how the installer finds the shells used on the system, since a
+/// shell that is installed but has never been configured would not read
+/// anything written by the installer, and the configuration file of a shell
+/// that has been uninstalled is not read at all.
+pub fn configured_shells() -> Result<Vec<(Shell, PathBuf)>> {
+    let mut shells = Vec::new();
+
+    for shell in Shell::value_variants() {
+        if !shell.is_installed() {
+            continue;
+        }
+
+        if let Some(file) = path_file(*shell)? {
+            shells.push((*shell, file));
+        }
+    }
+
+    Ok(shells)
+}
+
+/// Returns the configuration file of a shell in which the PATH is updated
+///
+/// This is the first file holding a block managed by the installer, so that
+/// the block is replaced rather than duplicated, or else the first existing
+/// file. It returns `None` if none of the files exists.
+pub fn path_file(shell: Shell) -> Result<Option<PathBuf>> {
+    let files = shell.config_files()?;
+
+    let managed = files.iter().find(|file| {
+        read_to_string(file)
+            .map(|content| content.lines().any(|line| line.trim() == BLOCK_START))
+            .unwrap_or(false)
+    });
+
+    Ok(managed
+        .or_else(|| files.iter().find(|file| file.exists()))
+        .cloned())
+}
+
+/// Finds the file login shells read instead of a configuration file, when it
+/// does not source it
+///
+/// Bash only reads the first existing of [BASH_LOGIN_FILES] when started as a
+/// login shell, as terminals do on macOS, so a line written to `.bashrc` is
+/// ignored by login shells unless that file sources it. Other shells read the
+/// same configuration file either way.
+pub fn login_file_skipping(shell: Shell, file: &Path) -> Option<PathBuf> {
+    if shell != Shell::Bash {
+        return None;
+    }
+
+    let home = file.parent()?;
+    let name = file.file_name()?.to_str()?;
+
+    let login = BASH_LOGIN_FILES
+        .iter()
+        .map(|login| home.join(login))
+        .find(|login| login.exists())?;
+
+    if login == file {
+        return None;
+    }
+
+    let sourced = read_to_string(&login).ok()?.lines().any(|line| {
+        let line = line.trim_start();
+        !line.starts_with('#') && line.contains(name)
+    });
+
+    (!sourced).then_some(login)
+}
+
+/// Checks whether a configuration file already prepends a directory to the
+/// PATH, in which case [write_path_block] leaves it untouched
+pub fn has_path_entry(shell: Shell, file: &Path, dir: &Path) -> Result<bool> {
+    let content = read_to_string(file).map_err(io_error(file))?;
+
+    Ok(updated_content(shell, &content, dir)?.is_none())
+}
+
+/// Writes the block that prepends a directory to the PATH to a configuration
+/// file of a shell
+///
+/// The block is delimited by [BLOCK_START] and [BLOCK_END]. If the file
+/// already holds one, it is replaced in place, so that running the installer
+/// again with another directory does not add another line. The lines written
+/// below [PATH_COMMENT] by previous versions are migrated the same way: the
+/// first one is replaced by the block, and the others are removed. Otherwise,
+/// the block is appended to the file, unless the directory is already
+/// prepended to the PATH by a line of its own. The rest of the file is kept
+/// byte for byte.
+pub fn write_path_block(shell: Shell, file: &Path, dir: &Path) -> Result<UpdateType> {
+    let content = read_to_string(file)
+        .map_err(io_error(file))
+        .context("Could not read the shell configuration file")?;
+
+    let Some(content) = updated_content(shell, &content, dir)? else {
+        return Ok(UpdateType::AlreadyInPath);
+    };
+
+    write(file, content)
+        .map_err(io_error(file))
+        .context("Could not update the shell configuration file")?;
+
+    Ok(UpdateType::Success)
+}
+
+/// Computes the content of a configuration file once the block prepending a
+/// directory to the PATH is written to it
+///
+/// It returns `None` if the file does not need to be updated.
+fn updated_content(shell: Shell, content: &str, dir: &Path) -> Result<Option<String>> {
+    let line = shell.prepend_command(dir);
+    let block = format!("{BLOCK_START}\n{line}\n{BLOCK_END}\n");
+
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let blocks = blocks(&lines);
+
+    if lines
+        .iter()
+        .filter(|line| line.trim() == BLOCK_START)
+        .count()
+        != blocks.len()
+    {
+        bail!("The block managed by the installer is not closed by \"{BLOCK_END}\"");
+    }
+
+    let legacy = legacy_entries(shell, &lines);
+
+    if blocks.is_empty() && !legacy.is_empty() {
+        return Ok(Some(migrated_content(&lines, &legacy, &block)));
+    }
+
+    let Some(range) = blocks.first().cloned() else {
+        if content.lines().any(|existing| existing.trim() == line) {
+            return Ok(None);
+        }
+
+        let mut content = content.to_string();
+
+        if !content.is_empty() {
+            if !content.ends_with('\n') {
+                content.push('\n');
+            }
+
+            content.push('\n');
+        }
+
+        content.push_str(&block);
+
+        return Ok(Some(content));
+    };
+
+    let current: String = lines[range.clone()].concat();
+
+    if current.trim_end() == block.trim_end() {
+        return Ok(None);
+    }
+
+    // The last line of the file may not end with a newline, in which case
+    // the block does not either
+    let replacement = match current.ends_with('\n') {
+        true => block.as_str(),
+        false => block.trim_end_matches('\n'),
+    };
+
+    Ok(Some(format!(
+        "{}{replacement}{}",
+        lines[..range.start].concat(),
+        lines[range.end..].concat()
+    )))
+}
+
+/// Replaces the lines written below [PATH_COMMENT] by previous versions of the
+/// installer with a block
+///
+/// The block takes the place of the first entry, along with its comment, and
+/// the other entries are removed.
+fn migrated_content(lines: &[&str], legacy: &[usize], block: &str) -> String {
+    let mut content = String::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let is_comment = legacy.contains(&(index + 1));
+        let is_entry = legacy.contains(&index);
+
+        if !is_comment && !is_entry {
+            content.push_str(line);
+        } else if legacy.first() == Some(&index) {
+            // The last line of the file may not end with a newline, in which
+            // case the block does not either
+            match line.ends_with('\n') {
+                true => content.push_str(block),
+                false => content.push_str(block.trim_end_matches('\n')),
+            }
+        }
+    }
+
+    content
+}
+
+/// Finds the lines written below [PATH_COMMENT] by previous versions of the
+/// installer
+///
+/// It returns the index of each line that prepends a directory to the PATH
+/// right below the comment. The comment itself is the line above.
+fn legacy_entries(shell: Shell, lines: &[&str]) -> Vec<usize> {
+    let comment = format!("# {PATH_COMMENT}");
+
+    (1..lines.len())
+        .filter(|index| lines[index - 1].trim() == comment)
+        .filter(|index| shell.parse_prepend_command(lines[*index].trim()).is_some())
+        .collect()
+}
+
+/// Finds the blocks managed by the installer among the lines of a file
+///
+/// Each range spans the lines from [BLOCK_START] to [BLOCK_END] included. A
+/// block that is not closed is ignored.
+fn blocks(lines: &[&str]) -> Vec<Range<usize>> {
+    let mut blocks = Vec::new();
+    let mut start = None;
+
+    for (index, line) in lines.iter().enumerate() {
+        match line.trim() {
+            BLOCK_START => start = Some(index),
+            BLOCK_END => {
+                if let Some(start) = start.take() {
+                    blocks.push(start..index + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    blocks
+}
+
+/// Computes the path of the backup of a shell configuration file
+fn backup_path(file: &Path) -> PathBuf {
+    let mut name = file.as_os_str().to_owned();
+    name.push(".biome-installer.bak");
+
+    PathBuf::from(name)
+}
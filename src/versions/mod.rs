@@ -0,0 +1,456 @@
+use anyhow::{Context, Result, anyhow};
+use clap::ValueEnum;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::{http::Http, npm::NpmRegistry};
+
+/// The base URL from which lists of versions are fetched by default
+pub const DEFAULT_VERSIONS_MIRROR: &str = "https://biomejs.dev/api/versions";
+
+/// The base URL of the GitHub API
+const GITHUB_API: &str = "https://api.github.com";
+
+/// How many pages of releases are read from the GitHub API at most
+const GITHUB_MAX_PAGES: u32 = 10;
+
+/// A list of versions of Biome
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VersionList {
+    /// The latest stable version
+    Latest,
+
+    /// Every stable version
+    Stable,
+
+    /// Every version, including pre-releases
+    Preview,
+}
+
+/// The sources of versions to use
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionProviderKind {
+    /// Our version API, falling back to GitHub Releases
+    #[default]
+    Auto,
+
+    /// Our version API only
+    Website,
+
+    /// GitHub Releases only
+    Github,
+
+    /// The npm registry only
+    Npm,
+}
+
+/// A source of the published versions of Biome
+pub trait VersionProvider {
+    /// Returns the name of the source, as displayed to the user
+    fn name(&self) -> &'static str;
+
+    /// Fetches the specified list of versions, from the most recent
+    ///
+    /// With `until`, the list only needs to go down to that version, which
+    /// lets the sources that page through their versions stop early.
+    fn fetch(&self, list: VersionList, until: Option<&Version>) -> Result<Vec<Version>>;
+}
+
+/// Our version API, which serves lists of versions as text files
+pub struct Website {
+    http: Http,
+    mirror: String,
+}
+
+/// The releases of the Biome repository on GitHub
+pub struct GitHubReleases {
+    http: Http,
+
+    /// The base URL of the GitHub API
+    api: String,
+}
+
+/// A release, as returned by the GitHub API
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    draft: bool,
+    prerelease: bool,
+}
+
+impl VersionList {
+    /// Returns the name of the file holding this list
+    ///
+    /// This is the name under which our version API serves the list, and
+    /// under which it is cached.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            VersionList::Latest => "latest.txt",
+            VersionList::Stable => "stable.txt",
+            VersionList::Preview => "preview.txt",
+        }
+    }
+}
+
+impl VersionProviderKind {
+    /// Creates the providers to query, in order
+    ///
+    /// `mirror` is the base URL of our version API, and `registry` the URL
+    /// of the npm registry, when they are not the default ones.
+    pub fn providers(
+        &self,
+        http: &Http,
+        mirror: Option<&str>,
+        registry: Option<&str>,
+    ) -> Vec<Box<dyn VersionProvider>> {
+        let website =
+            || -> Box<dyn VersionProvider> { Box::new(Website::new(http.clone(), mirror)) };
+        let github = || -> Box<dyn VersionProvider> { Box::new(GitHubReleases::new(http.clone())) };
+        let npm =
+            || -> Box<dyn VersionProvider> { Box::new(NpmRegistry::new(http.clone(), registry)) };
+
+        match self {
+            VersionProviderKind::Auto => vec![website(), github()],
+            VersionProviderKind::Website => vec![website()],
+            VersionProviderKind::Github => vec![github()],
+            VersionProviderKind::Npm => vec![npm()],
+        }
+    }
+}
+
+impl Website {
+    /// Creates the provider, for the specified mirror of our version API
+    pub fn new(http: Http, mirror: Option<&str>) -> Self {
+        Website {
+            http,
+            mirror: mirror
+                .unwrap_or(DEFAULT_VERSIONS_MIRROR)
+                .trim_end_matches('/')
+                .to_string(),
+        }
+    }
+}
+
+impl VersionProvider for Website {
+    fn name(&self) -> &'static str {
+        "the Biome version API"
+    }
+
+    fn fetch(&self, list: VersionList, _until: Option<&Version>) -> Result<Vec<Version>> {
+        let url = format!("{}/{}", self.mirror, list.file_name());
+
+        parse_version_list(&self.http.text(&url)?)
+            .with_context(|| format!("Invalid list of versions at {url}"))
+    }
+}
+
+impl GitHubReleases {
+    /// Creates the provider
+    pub fn new(http: Http) -> Self {
+        GitHubReleases {
+            http,
+            api: GITHUB_API.to_string(),
+        }
+    }
+
+    /// Fetches the published releases of Biome, along with whether they are
+    /// pre-releases
+    ///
+    /// Releases are listed from the most recent, and pages are read until
+    /// `done` returns `true` for the releases read so far, or until there are
+    /// no more. The repository also hosts releases of other packages, whose
+    /// tags are skipped, as well as drafts.
+    fn releases(&self, done: impl Fn(&[(Version, bool)]) -> bool) -> Result<Vec<(Version, bool)>> {
+        let mut releases = Vec::new();
+
+        for page in 1..=GITHUB_MAX_PAGES {
+            let url = format!(
+                "{}/repos/biomejs/biome/releases?per_page=100&page={page}",
+                self.api
+            );
+
+            let page: Vec<Release> = serde_json::from_str(&self.http.text(&url)?)
+                .with_context(|| format!("Invalid list of releases at {url}"))?;
+
+            if page.is_empty() {
+                break;
+            }
+
+            releases.extend(
+                page.into_iter()
+                    .filter(|release| !release.draft)
+                    .filter_map(|release| {
+                        let version = parse_git_tag(&release.tag_name)?;
+                        let prerelease = release.prerelease || !version.pre.is_empty();
+
+                        Some((version, prerelease))
+                    }),
+            );
+
+            if done(&releases) {
+                break;
+            }
+        }
+
+        releases.sort_by(|a, b| b.0.cmp(&a.0));
+
+        Ok(releases)
+    }
+}
+
+impl VersionProvider for GitHubReleases {
+    fn name(&self) -> &'static str {
+        "GitHub Releases"
+    }
+
+    fn fetch(&self, list: VersionList, until: Option<&Version>) -> Result<Vec<Version>> {
+        let releases = self
+            .releases(|releases| match (list, until) {
+                (VersionList::Latest, _) => releases.iter().any(|(_, prerelease)| !prerelease),
+                (_, Some(until)) => releases.iter().any(|(version, _)| version == until),
+                (_, None) => false,
+            })?
+            .into_iter();
+
+        let versions: Vec<Version> = match list {
+            VersionList::Latest => releases
+                .filter(|(_, prerelease)| !prerelease)
+                .map(|(version, _)| version)
+                .take(1)
+                .collect(),
+            VersionList::Stable => releases
+                .filter(|(_, prerelease)| !prerelease)
+                .map(|(version, _)| version)
+                .collect(),
+            VersionList::Preview => releases.map(|(version, _)| version).collect(),
+        };
+
+        Ok(versions)
+    }
+}
+
+/// Fetches a list of versions from the first provider that succeeds
+///
+/// `until` is passed on to [VersionProvider::fetch]. `on_failure` is called
+/// for every provider that fails before the next one is tried. If every
+/// provider fails, the last error is returned.
+pub fn fetch_with_fallback(
+    providers: &[Box<dyn VersionProvider>],
+    list: VersionList,
+    until: Option<&Version>,
+    mut on_failure: impl FnMut(&dyn VersionProvider, &anyhow::Error),
+) -> Result<Vec<Version>> {
+    let mut last_error = anyhow!("No source of versions is configured");
+
+    for (index, provider) in providers.iter().enumerate() {
+        last_error = match provider.fetch(list, until) {
+            Ok(versions) if !versions.is_empty() => return Ok(versions),
+            Ok(_) => anyhow!("{} has no versions", provider.name()),
+            Err(err) => err,
+        };
+
+        if index + 1 < providers.len() {
+            on_failure(provider.as_ref(), &last_error);
+        }
+    }
+
+    Err(last_error)
+}
+
+/// Finds the closest version to the specified one that has a build for the
+/// current platform, according to `has_build`
+///
+/// Biome only ever adds platforms, so the closest version with a build is the
+/// oldest more recent one that has it, which is found with a binary search to
+/// keep the number of lookups low. Pre-releases are only considered when the
+/// specified version is itself a pre-release.
+pub fn closest_version(
+    versions: &[Version],
+    version: &Version,
+    mut has_build: impl FnMut(&Version) -> Result<bool>,
+) -> Result<Option<Version>> {
+    let mut candidates: Vec<&Version> = versions
+        .iter()
+        .filter(|candidate| *candidate > version)
+        .filter(|candidate| candidate.pre.is_empty() || !version.pre.is_empty())
+        .collect();
+
+    candidates.sort();
+
+    let (mut low, mut high) = (0, candidates.len());
+
+    while low < high {
+        let middle = low + (high - low) / 2;
+
+        match has_build(candidates[middle])? {
+            true => high = middle,
+            false => low = middle + 1,
+        }
+    }
+
+    Ok(candidates.get(low).map(|closest| (*closest).clone()))
+}
+
+/// Parses a list of versions, one per line
+pub fn parse_version_list(list: &str) -> Result<Vec<Version>> {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| Version::parse(line).with_context(|| format!("Invalid version: {line}")))
+        .collect()
+}
+
+/// Parses the version of Biome from a git tag
+///
+/// This is the inverse of [crate::downloader::Downloader::get_git_tag]: it
+/// understands both the `cli/vX.Y.Z` and `@biomejs/biome@X.Y.Z` formats, and
+/// returns `None` for the tags of other packages.
+pub fn parse_git_tag(tag: &str) -> Option<Version> {
+    let version = tag
+        .strip_prefix("cli/v")
+        .or_else(|| tag.strip_prefix("@biomejs/biome@"))?;
+
+    Version::parse(version).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use super::*;
+    use crate::http::HttpOptions;
+
+    /// Starts a server that answers one request with each of the given
+    /// responses, and returns its URL along with a handle yielding the
+    /// requests it received
+    fn start_server(responses: &[&str]) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let responses: Vec<String> = responses.iter().map(|body| body.to_string()).collect();
+
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request = String::new();
+                while reader.read_line(&mut request).unwrap() > 0 && !request.ends_with("\r\n\r\n")
+                {
+                }
+
+                stream.write_all(response.as_bytes()).unwrap();
+                requests.push(request);
+            }
+
+            requests
+        });
+
+        (url, handle)
+    }
+
+    fn ok(body: &str) -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    fn release(tag: &str, prerelease: bool) -> String {
+        format!(r#"{{"tag_name":"{tag}","draft":false,"prerelease":{prerelease}}}"#)
+    }
+
+    #[test]
+    fn it_parses_the_tags_of_biome() {
+        assert_eq!(
+            parse_git_tag("@biomejs/biome@2.0.6"),
+            Some(Version::new(2, 0, 6))
+        );
+        assert_eq!(
+            parse_git_tag("@biomejs/biome@2.1.0-beta.1"),
+            Version::parse("2.1.0-beta.1").ok()
+        );
+        assert_eq!(parse_git_tag("cli/v1.9.4"), Some(Version::new(1, 9, 4)));
+    }
+
+    #[test]
+    fn it_skips_the_tags_of_other_packages() {
+        assert_eq!(parse_git_tag("@biomejs/js-api@1.0.0"), None);
+        assert_eq!(parse_git_tag("lsp/v2.0.0"), None);
+        assert_eq!(parse_git_tag("cli/vnext"), None);
+        assert_eq!(parse_git_tag("v2.0.6"), None);
+    }
+
+    #[test]
+    fn it_falls_back_to_github_releases_and_stops_at_the_latest_stable_release() {
+        let page = format!(
+            "[{},{},{}]",
+            release("@biomejs/biome@2.1.0-beta.1", true),
+            release("@biomejs/js-api@1.0.0", false),
+            release("@biomejs/biome@2.0.6", false)
+        );
+        let (server, handle) = start_server(&[
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            &ok(&page),
+        ]);
+
+        let http = HttpOptions::default().client().unwrap();
+        let providers: Vec<Box<dyn VersionProvider>> = vec![
+            Box::new(Website::new(http.clone(), Some(&server))),
+            Box::new(GitHubReleases {
+                http,
+                api: server.clone(),
+            }),
+        ];
+
+        let mut failed = Vec::new();
+        let versions = fetch_with_fallback(&providers, VersionList::Latest, None, |provider, _| {
+            failed.push(provider.name())
+        })
+        .unwrap();
+
+        assert_eq!(versions, [Version::new(2, 0, 6)]);
+        assert_eq!(failed, ["the Biome version API"]);
+
+        let requests = handle.join().unwrap();
+
+        assert!(requests[0].starts_with("GET /latest.txt "));
+        assert!(requests[1].starts_with("GET /repos/biomejs/biome/releases?per_page=100&page=1 "));
+    }
+
+    #[test]
+    fn it_stops_paging_through_releases_at_the_requested_version() {
+        let first = format!(
+            "[{},{}]",
+            release("@biomejs/biome@2.0.6", false),
+            release("@biomejs/biome@2.0.5", false)
+        );
+        let second = format!("[{}]", release("@biomejs/biome@2.0.4", false));
+        let (server, handle) = start_server(&[&ok(&first), &ok(&second)]);
+
+        let releases = GitHubReleases {
+            http: HttpOptions::default().client().unwrap(),
+            api: server,
+        };
+
+        let versions = releases
+            .fetch(VersionList::Preview, Some(&Version::new(2, 0, 4)))
+            .unwrap();
+
+        assert_eq!(
+            versions,
+            [
+                Version::new(2, 0, 6),
+                Version::new(2, 0, 5),
+                Version::new(2, 0, 4)
+            ]
+        );
+        assert_eq!(handle.join().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,78 @@
+#![cfg(unix)]
+
+mod common;
+
+use assert_cmd::Command;
+use common::{asset_name, cache_fake_biome, run_json};
+
+#[test]
+pub fn it_installs_the_most_recent_version_from_a_bundle() {
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    let work_dir = assert_fs::TempDir::new().unwrap();
+    let install_dir = work_dir.path().join("bin");
+    let bundle = work_dir.path().join("biome-bundle.tar.gz");
+    let asset = asset_name();
+
+    cache_fake_biome(cache_dir.path(), "2.0.5", &asset);
+    cache_fake_biome(cache_dir.path(), "2.0.6", &asset);
+
+    let report = run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["bundle", "--version", "2.0.5,2.0.6", "--target", &asset])
+            .arg("--file")
+            .arg(&bundle)
+            .arg("--cache-dir")
+            .arg(cache_dir.path()),
+    );
+
+    assert_eq!(report["assets"].as_array().unwrap().len(), 2);
+    assert_eq!(report["assets"][1]["path"], format!("2.0.6/{asset}"));
+
+    let report = run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--no-prepend-path", "--bundle"])
+            .arg(&bundle)
+            .arg("--install-dir")
+            .arg(&install_dir),
+    );
+
+    assert_eq!(report["version"], "2.0.6");
+    assert_eq!(report["version_source"], "bundle");
+    assert_eq!(
+        report["asset_url"],
+        format!("{}#2.0.6/{asset}", bundle.display())
+    );
+    assert!(install_dir.join("biome").exists());
+}
+
+#[test]
+pub fn it_fails_when_the_version_is_not_in_the_bundle() {
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    let work_dir = assert_fs::TempDir::new().unwrap();
+    let bundle = work_dir.path().join("biome-bundle.tar.gz");
+    let asset = asset_name();
+
+    cache_fake_biome(cache_dir.path(), "2.0.6", &asset);
+
+    run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["bundle", "--version", "2.0.6", "--target", &asset])
+            .arg("--file")
+            .arg(&bundle)
+            .arg("--cache-dir")
+            .arg(cache_dir.path()),
+    );
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--version", "2.0.5", "--bundle"])
+        .arg(&bundle)
+        .arg("--no-prepend-path")
+        .arg("--install-dir")
+        .arg(work_dir.path().join("bin"))
+        .assert()
+        .code(4);
+}
@@ -0,0 +1,209 @@
+#![cfg(unix)]
+
+mod common;
+
+use assert_cmd::Command;
+use common::{asset_name, cache_fake_biome, run_json};
+
+#[test]
+pub fn it_installs_from_the_cache() {
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    let install_dir = assert_fs::TempDir::new().unwrap();
+
+    cache_fake_biome(cache_dir.path(), "2.0.6", &asset_name());
+
+    let report = run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--version", "2.0.6", "--no-prepend-path"])
+            .arg("--install-dir")
+            .arg(install_dir.path())
+            .arg("--cache-dir")
+            .arg(cache_dir.path()),
+    );
+
+    assert_eq!(report["cached"], true);
+    assert!(install_dir.path().join("biome").exists());
+}
+
+#[test]
+pub fn it_lists_and_prunes_cached_binaries() {
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+
+    cache_fake_biome(cache_dir.path(), "1.9.4", "biome-linux-x64");
+    cache_fake_biome(cache_dir.path(), "2.0.5", "biome-linux-x64");
+    cache_fake_biome(cache_dir.path(), "2.0.6", "biome-linux-x64");
+
+    let cache = |args: &[&str]| {
+        run_json(
+            Command::cargo_bin("biome-installer")
+                .unwrap()
+                .arg("cache")
+                .args(args)
+                .arg("--cache-dir")
+                .arg(cache_dir.path()),
+        )
+    };
+
+    let list = cache(&["list"]);
+
+    assert_eq!(list["entries"].as_array().unwrap().len(), 3);
+    assert_eq!(list["entries"][0]["version"], "2.0.6");
+
+    let prune = cache(&["prune", "--keep", "2"]);
+
+    assert_eq!(prune["removed"].as_array().unwrap().len(), 1);
+    assert_eq!(prune["removed"][0]["version"], "1.9.4");
+    assert!(!cache_dir.path().join("assets/1.9.4").exists());
+
+    let clean = cache(&["clean"]);
+
+    assert_eq!(clean["removed"].as_array().unwrap().len(), 2);
+    assert!(!cache_dir.path().join("assets").exists());
+}
+
+#[test]
+pub fn it_resolves_the_latest_version_from_the_cache_when_offline() {
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    let install_dir = assert_fs::TempDir::new().unwrap();
+
+    cache_fake_biome(cache_dir.path(), "2.0.6", &asset_name());
+    std::fs::create_dir_all(cache_dir.path().join("versions")).unwrap();
+    std::fs::write(cache_dir.path().join("versions/latest.txt"), "2.0.6\n").unwrap();
+
+    let report = run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--offline", "--no-prepend-path"])
+            .arg("--install-dir")
+            .arg(install_dir.path())
+            .arg("--cache-dir")
+            .arg(cache_dir.path()),
+    );
+
+    assert_eq!(report["version"], "2.0.6");
+    assert_eq!(report["version_source"], "latest");
+    assert_eq!(report["cached"], true);
+}
+
+#[test]
+pub fn it_fails_when_offline_and_not_in_the_cache() {
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    let install_dir = assert_fs::TempDir::new().unwrap();
+
+    let output = Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args([
+            "install",
+            "--offline",
+            "--version",
+            "2.0.6",
+            "--install-dir",
+        ])
+        .arg(install_dir.path())
+        .arg("--cache-dir")
+        .arg(cache_dir.path())
+        .args(["--output", "json"])
+        .assert()
+        .code(11)
+        .get_output()
+        .clone();
+
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+
+    assert_eq!(error["error"]["code"], "not_in_cache");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn it_diagnoses_a_cached_binary_that_does_not_run() {
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    let install_dir = assert_fs::TempDir::new().unwrap();
+
+    common::cache_binary(
+        cache_dir.path(),
+        "2.0.6",
+        &asset_name(),
+        "#!/bin/sh\nexit 127\n",
+    );
+
+    let install = |args: &[&str]| {
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args([
+                "install",
+                "--offline",
+                "--version",
+                "2.0.6",
+                "--no-prepend-path",
+            ])
+            .args(args)
+            .arg("--install-dir")
+            .arg(install_dir.path())
+            .arg("--cache-dir")
+            .arg(cache_dir.path())
+            .args(["--output", "json"])
+            .assert()
+    };
+
+    let output = install(&[]).code(9).get_output().clone();
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    let message = error["error"]["message"].as_str().unwrap();
+
+    assert_eq!(error["error"]["code"], "broken_binary");
+    assert!(message.contains("re-run with `--libc "));
+    assert!(!install_dir.path().join("biome").exists());
+
+    // The other build is not downloaded in offline mode
+    let other = message
+        .split("`--libc ")
+        .nth(1)
+        .and_then(|rest| rest.split('`').next())
+        .unwrap();
+
+    install(&["--libc", other]).code(11);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn it_does_not_blame_the_libc_for_a_binary_that_fails() {
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    let install_dir = assert_fs::TempDir::new().unwrap();
+
+    common::cache_binary(
+        cache_dir.path(),
+        "2.0.6",
+        &asset_name(),
+        "#!/bin/sh\nexit 1\n",
+    );
+
+    let output = Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args([
+            "install",
+            "--offline",
+            "--version",
+            "2.0.6",
+            "--no-prepend-path",
+        ])
+        .arg("--install-dir")
+        .arg(install_dir.path())
+        .arg("--cache-dir")
+        .arg(cache_dir.path())
+        .args(["--output", "json"])
+        .assert()
+        .code(9)
+        .get_output()
+        .clone();
+
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+
+    assert_eq!(error["error"]["code"], "broken_binary");
+    assert!(
+        error["error"]["causes"][0]
+            .as_str()
+            .unwrap()
+            .ends_with("`biome --version` exited with exit status: 1")
+    );
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("libc"));
+}
@@ -0,0 +1,142 @@
+//! Helpers shared by the integration tests
+//!
+//! Each test file is compiled as its own crate and only uses some of them.
+#![allow(dead_code)]
+
+use std::{
+    ffi::OsString,
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    path::Path,
+    thread,
+};
+
+use assert_cmd::Command;
+use sha2::{Digest, Sha256};
+
+/// The environment variables that point to files of the current user outside
+/// of the home directory
+const HOME_VARIABLES: &[&str] = &["XDG_CONFIG_HOME", "XDG_CACHE_HOME", "ZDOTDIR"];
+
+/// Runs the installer with `--output json`, checks that it succeeds, and
+/// returns the report it printed
+///
+/// Unless the command sets them, the `BIOME_*` environment variables of the
+/// test runner and those in [HOME_VARIABLES] are removed, and the home
+/// directory is a temporary one, so that the installer does not read or write
+/// the files of the current user.
+pub fn run_json(command: &mut Command) -> serde_json::Value {
+    let home = assert_fs::TempDir::new().unwrap();
+    let set: Vec<OsString> = command.get_envs().map(|(key, _)| key.to_owned()).collect();
+
+    for (key, _) in std::env::vars_os() {
+        if key.to_string_lossy().starts_with("BIOME_") && !set.contains(&key) {
+            command.env_remove(key);
+        }
+    }
+
+    if !set.iter().any(|key| key == "HOME") {
+        command.env("HOME", home.path());
+    }
+
+    for name in HOME_VARIABLES {
+        if !set.iter().any(|key| key == name) {
+            command.env_remove(name);
+        }
+    }
+
+    let output = command
+        .args(["--output", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// Stores a fake Biome binary that reports the given version in the cache
+pub fn cache_fake_biome(cache_dir: &Path, version: &str, asset: &str) {
+    cache_binary(
+        cache_dir,
+        version,
+        asset,
+        &format!("#!/bin/sh\necho \"Version: {version}\"\n"),
+    );
+}
+
+/// Stores a binary with the given content in the cache
+pub fn cache_binary(cache_dir: &Path, version: &str, asset: &str, content: &str) {
+    let dir = cache_dir.join("assets").join(version);
+
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(asset), content).unwrap();
+    std::fs::write(
+        dir.join(format!("{asset}.sha256")),
+        format!("{:x}", Sha256::digest(content.as_bytes())),
+    )
+    .unwrap();
+}
+
+/// Returns the name of the asset for the current platform
+pub fn asset_name() -> String {
+    let plan = run_json(Command::cargo_bin("biome-installer").unwrap().args([
+        "install",
+        "--version",
+        "2.0.6",
+        "--dry-run",
+    ]));
+
+    plan["asset"].as_str().unwrap().to_string()
+}
+
+/// Starts a server that answers one request with each of the given
+/// responses, and returns its URL along with a handle yielding the requests
+/// it received
+pub fn start_server<R: Into<Vec<u8>>>(
+    responses: impl IntoIterator<Item = R>,
+) -> (String, thread::JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    (url, serve(listener, responses))
+}
+
+/// Answers one request on the given listener with each of the given
+/// responses, and returns a handle yielding the requests it received
+///
+/// This is useful when the responses need the URL of the server.
+pub fn serve<R: Into<Vec<u8>>>(
+    listener: TcpListener,
+    responses: impl IntoIterator<Item = R>,
+) -> thread::JoinHandle<Vec<String>> {
+    let responses: Vec<Vec<u8>> = responses.into_iter().map(Into::into).collect();
+
+    thread::spawn(move || {
+        let mut requests = Vec::new();
+
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut request = String::new();
+            while reader.read_line(&mut request).unwrap() > 0 && !request.ends_with("\r\n\r\n") {}
+
+            stream.write_all(&response).unwrap();
+            requests.push(request);
+        }
+
+        requests
+    })
+}
+
+/// Builds a `200 OK` response with the given body
+pub fn ok(body: &[u8]) -> Vec<u8> {
+    let mut response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )
+    .into_bytes();
+    response.extend_from_slice(body);
+    response
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use assert_cmd::Command;
+use assert_fs::prelude::{FileWriteStr, PathChild};
+use common::run_json;
+use predicates::prelude::PredicateBooleanExt;
+
+fn dry_run(config: &std::path::Path, args: &[&str]) -> serde_json::Value {
+    run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--version", "2.0.6", "--dry-run"])
+            .args(args)
+            .env("BIOME_CONFIG", config),
+    )
+}
+
+#[test]
+pub fn it_sets_values_and_preserves_comments() {
+    let dir = assert_fs::TempDir::new().unwrap();
+    let config = dir.child("config.toml");
+    config
+        .write_str("# Managed by ops\nchannel = \"stable\" # or preview\n")
+        .unwrap();
+
+    for args in [
+        ["set", "prepend_path", "false"],
+        ["set", "channel", "preview"],
+    ] {
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .arg("config")
+            .args(args)
+            .env("BIOME_CONFIG", config.path())
+            .assert()
+            .success();
+    }
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["config", "set", "channel", "nightly"])
+        .env("BIOME_CONFIG", config.path())
+        .assert()
+        .failure();
+
+    let report = run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["config", "get", "prepend_path"])
+            .env("BIOME_CONFIG", config.path()),
+    );
+
+    assert_eq!(report["value"], false);
+    assert_eq!(
+        std::fs::read_to_string(config.path()).unwrap(),
+        "# Managed by ops\nchannel = \"preview\" # or preview\nprepend_path = false\n"
+    );
+}
+
+#[test]
+pub fn it_rejects_unknown_keys() {
+    let dir = assert_fs::TempDir::new().unwrap();
+    let config = dir.child("config.toml");
+    config.write_str("colour = \"blue\"\n").unwrap();
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--version", "2.0.6", "--dry-run"])
+        .env("BIOME_CONFIG", config.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Invalid configuration file"));
+}
+
+#[test]
+pub fn it_gives_precedence_to_flags_over_the_configuration() {
+    let dir = assert_fs::TempDir::new().unwrap();
+    let config = dir.child("config.toml");
+    let from_config = dir.path().join("from-config");
+    let from_flag = dir.path().join("from-flag");
+
+    config
+        .write_str(&format!(
+            "install_dir = {:?}\ndownload_mirror = \"https://mirror.example.com/biome/\"\nprepend_path = false\n",
+            from_config.display().to_string()
+        ))
+        .unwrap();
+
+    let plan = dry_run(config.path(), &[]);
+
+    assert_eq!(
+        plan["destination"],
+        from_config.join("biome").display().to_string()
+    );
+    assert_eq!(
+        plan["asset_url"],
+        format!(
+            "https://mirror.example.com/biome/@biomejs/biome@2.0.6/{}",
+            plan["asset"].as_str().unwrap()
+        )
+    );
+    assert_eq!(plan["path_update"], serde_json::Value::Null);
+
+    let plan = dry_run(
+        config.path(),
+        &["--install-dir", from_flag.to_str().unwrap()],
+    );
+
+    assert_eq!(
+        plan["destination"],
+        from_flag.join("biome").display().to_string()
+    );
+}
+
+#[test]
+pub fn it_gives_precedence_to_flags_and_environment_variables_over_booleans() {
+    let dir = assert_fs::TempDir::new().unwrap();
+    let config = dir.child("config.toml");
+    config
+        .write_str("prepend_path = false\ninsecure = true\n")
+        .unwrap();
+
+    let bashrc = dir.child(".bashrc");
+    bashrc.write_str("").unwrap();
+
+    let plan = |args: &[&str]| {
+        run_json(
+            Command::cargo_bin("biome-installer")
+                .unwrap()
+                .args(["install", "--version", "2.0.6", "--dry-run"])
+                .args(args)
+                .env("BIOME_CONFIG", config.path())
+                .env("HOME", dir.path())
+                .env("SHELL", "/bin/bash"),
+        )
+    };
+
+    for args in [["--shells", "bash"], ["--prepend-path", "--no-insecure"]] {
+        assert_eq!(
+            plan(&args)["path_updates"][0]["target"],
+            bashrc.path().display().to_string()
+        );
+    }
+
+    let install = || {
+        let mut command = Command::cargo_bin("biome-installer").unwrap();
+        command
+            .args(["install", "--version", "2.0.6", "--dry-run"])
+            .env("BIOME_CONFIG", config.path())
+            .env_remove("BIOME_INSTALL_DIR");
+        command
+    };
+
+    install()
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "TLS certificates will not be verified, as requested in the configuration.",
+        ));
+
+    install()
+        .env("BIOME_INSECURE", "false")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("TLS certificates").not());
+
+    install()
+        .env("BIOME_INSECURE", "false")
+        .arg("--insecure")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "TLS certificates will not be verified, as requested with --insecure.",
+        ));
+}
@@ -0,0 +1,241 @@
+#![cfg(unix)]
+
+mod common;
+
+use assert_cmd::Command;
+use assert_fs::prelude::{FileTouch, PathChild};
+use common::run_json;
+
+#[test]
+pub fn it_prints_the_installation_plan_without_installing() {
+    let home = assert_fs::TempDir::new().unwrap();
+
+    let shell_config = home.child(".zshrc");
+    shell_config.touch().unwrap();
+
+    let plan = run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--version", "2.0.6", "--dry-run"])
+            .env("HOME", home.path())
+            .env("SHELL", "/bin/zsh"),
+    );
+
+    assert_eq!(plan["dry_run"], true);
+    assert_eq!(plan["version"], "2.0.6");
+    assert_eq!(plan["version_source"], "argument");
+    assert_eq!(plan["git_tag"], "@biomejs/biome@2.0.6");
+    assert_eq!(
+        plan["destination"],
+        home.path().join(".biome/bin/biome").display().to_string()
+    );
+    assert_eq!(
+        plan["path_update"]["line"],
+        format!(
+            "export PATH=\"{}:$PATH\"",
+            home.path().join(".biome/bin").display()
+        )
+    );
+
+    assert!(!home.path().join(".biome").exists());
+    assert_eq!(std::fs::read_to_string(&shell_config).unwrap(), "");
+}
+
+#[test]
+pub fn it_uses_the_legacy_tag_scheme_for_biome_1() {
+    let home = assert_fs::TempDir::new().unwrap();
+
+    let plan = run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args([
+                "install",
+                "--version",
+                "1.9.4",
+                "--dry-run",
+                "--no-prepend-path",
+            ])
+            .env("HOME", home.path()),
+    );
+
+    assert_eq!(plan["git_tag"], "cli/v1.9.4");
+    assert_eq!(plan["path_update"], serde_json::Value::Null);
+}
+
+fn path_update(home: &std::path::Path, envs: &[(&str, &std::path::Path)]) -> serde_json::Value {
+    let plan = run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--version", "2.0.6", "--dry-run"])
+            .env("HOME", home)
+            .envs(envs.iter().copied()),
+    );
+
+    plan["path_update"].clone()
+}
+
+#[test]
+pub fn it_resolves_the_shell_configuration_files_like_the_shells_do() {
+    let home = assert_fs::TempDir::new().unwrap();
+
+    let zdotdir = home.child("zsh");
+    zdotdir.child(".zshrc").touch().unwrap();
+
+    let change = path_update(
+        home.path(),
+        &[("SHELL", "/bin/zsh".as_ref()), ("ZDOTDIR", zdotdir.path())],
+    );
+
+    assert_eq!(
+        change["target"],
+        zdotdir.child(".zshrc").path().display().to_string()
+    );
+
+    let config_home = home.child("config");
+    config_home.child("fish/config.fish").touch().unwrap();
+
+    let change = path_update(
+        home.path(),
+        &[
+            ("SHELL", "/usr/bin/fish".as_ref()),
+            ("XDG_CONFIG_HOME", config_home.path()),
+        ],
+    );
+
+    assert_eq!(
+        change["target"],
+        config_home
+            .child("fish/config.fish")
+            .path()
+            .display()
+            .to_string()
+    );
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn it_detects_when_login_shells_do_not_source_the_shell_configuration_file() {
+    use assert_fs::prelude::FileWriteStr;
+
+    let home = assert_fs::TempDir::new().unwrap();
+
+    home.child(".bashrc").touch().unwrap();
+    home.child(".bash_profile")
+        .write_str("# Does not read ~/.bashrc\nexport EDITOR=vim\n")
+        .unwrap();
+
+    let change = path_update(home.path(), &[("SHELL", "/bin/bash".as_ref())]);
+
+    assert_eq!(
+        change["target"],
+        home.child(".bashrc").path().display().to_string()
+    );
+    assert_eq!(
+        change["skipped_by"],
+        home.child(".bash_profile").path().display().to_string()
+    );
+
+    home.child(".bash_profile")
+        .write_str("[ -f ~/.bashrc ] && . ~/.bashrc\n")
+        .unwrap();
+
+    let change = path_update(home.path(), &[("SHELL", "/bin/bash".as_ref())]);
+
+    assert_eq!(change["skipped_by"], serde_json::Value::Null);
+}
+
+#[test]
+pub fn it_plans_the_path_update_of_each_selected_shell() {
+    let home = assert_fs::TempDir::new().unwrap();
+
+    home.child(".zshrc").touch().unwrap();
+    home.child(".tcshrc").touch().unwrap();
+
+    let plan = run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--version", "2.0.6", "--dry-run"])
+            .args(["--shells", "zsh,tcsh"])
+            .env("HOME", home.path())
+            .env("SHELL", "/bin/bash"),
+    );
+    let targets: Vec<&str> = plan["path_updates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|change| change["target"].as_str().unwrap())
+        .collect();
+
+    assert_eq!(
+        targets,
+        [
+            home.child(".zshrc").path().to_str().unwrap(),
+            home.child(".tcshrc").path().to_str().unwrap()
+        ]
+    );
+    assert_eq!(plan["path_update"], plan["path_updates"][0]);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn it_detects_the_shell_the_installer_is_run_from() {
+    let home = assert_fs::TempDir::new().unwrap();
+
+    home.child(".bashrc").touch().unwrap();
+    home.child(".config/fish/config.fish").touch().unwrap();
+
+    let plan = |args: &str| {
+        let output = std::process::Command::new("bash")
+            .arg("-c")
+            .arg(format!(
+                "'{}' install --version 2.0.6 --dry-run --output json {args}; true",
+                assert_cmd::cargo::cargo_bin("biome-installer").display()
+            ))
+            .env("HOME", home.path())
+            .env("SHELL", "/bin/zsh")
+            .env_remove("XDG_CONFIG_HOME")
+            .output()
+            .unwrap();
+
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let plan_of_bash = plan("");
+
+    assert_eq!(plan_of_bash["platform"]["shell"], "bash");
+    assert_eq!(
+        plan_of_bash["path_updates"][0]["target"],
+        home.child(".bashrc").path().display().to_string()
+    );
+
+    // The detected shell is reported, but the requested one is updated
+    let plan_of_fish = plan("--shell fish");
+
+    assert_eq!(plan_of_fish["platform"]["shell"], "bash");
+    assert_eq!(
+        plan_of_fish["path_updates"][0]["target"],
+        home.child(".config/fish/config.fish")
+            .path()
+            .display()
+            .to_string()
+    );
+}
+
+#[test]
+pub fn it_does_not_populate_the_cache() {
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    let (server, _) = common::start_server([
+        "HTTP/1.1 200 OK\r\nContent-Length: 6\r\nConnection: close\r\n\r\n2.0.6\n",
+    ]);
+
+    run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--dry-run", "--no-prepend-path"])
+            .args(["--versions-mirror", &server])
+            .arg("--cache-dir")
+            .arg(cache_dir.path()),
+    );
+
+    assert_eq!(std::fs::read_dir(cache_dir.path()).unwrap().count(), 0);
+}
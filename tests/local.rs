@@ -0,0 +1,100 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::path::Path;
+
+use assert_cmd::Command;
+use common::{asset_name, cache_fake_biome, run_json};
+
+fn install_locally(dir: &Path, cache_dir: &Path, args: &[&str]) -> serde_json::Value {
+    run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .current_dir(dir)
+            .args(["install", "--local"])
+            .args(args)
+            .arg("--cache-dir")
+            .arg(cache_dir),
+    )
+}
+
+#[test]
+pub fn it_installs_biome_into_the_project() {
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    let project = assert_fs::TempDir::new().unwrap();
+    let root = project.path().canonicalize().unwrap();
+    let nested = root.join("packages").join("app");
+
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(root.join("package.json"), "{}").unwrap();
+
+    let asset = asset_name();
+    cache_fake_biome(cache_dir.path(), "2.0.5", &asset);
+    cache_fake_biome(cache_dir.path(), "2.0.6", &asset);
+
+    let report = install_locally(&nested, cache_dir.path(), &["--version", "2.0.5"]);
+
+    assert_eq!(report["path_update"], "local");
+    assert_eq!(report["project"], root.to_str().unwrap());
+    assert!(root.join(".biome/bin/biome").exists());
+    assert_eq!(
+        std::fs::read_to_string(root.join(".biome-version")).unwrap(),
+        "2.0.5\n"
+    );
+
+    // The recorded version is installed when no version is specified
+    let report = install_locally(&root, cache_dir.path(), &[]);
+
+    assert_eq!(report["version"], "2.0.5");
+    assert_eq!(report["version_source"], "project");
+}
+
+#[test]
+pub fn it_fails_outside_of_a_project() {
+    let dir = assert_fs::TempDir::new().unwrap();
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["install", "--local", "--version", "2.0.6", "--no-cache"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Could not find the root of the project",
+        ));
+}
+
+#[test]
+pub fn it_takes_precedence_over_the_installation_directory_from_the_environment() {
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    let project = assert_fs::TempDir::new().unwrap();
+    let install_dir = assert_fs::TempDir::new().unwrap();
+    let root = project.path().canonicalize().unwrap();
+
+    std::fs::write(root.join("package.json"), "{}").unwrap();
+    cache_fake_biome(cache_dir.path(), "2.0.6", &asset_name());
+
+    run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .current_dir(&root)
+            .args(["install", "--local", "--version", "2.0.6"])
+            .arg("--cache-dir")
+            .arg(cache_dir.path())
+            .env("BIOME_INSTALL_DIR", install_dir.path()),
+    );
+
+    assert!(root.join(".biome/bin/biome").exists());
+    assert!(!install_dir.path().join("biome").exists());
+
+    // An installation directory given on the command line is a mistake
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .current_dir(&root)
+        .args(["install", "--local", "--version", "2.0.6"])
+        .arg("--install-dir")
+        .arg(install_dir.path())
+        .assert()
+        .code(2);
+}
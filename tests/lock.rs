@@ -0,0 +1,130 @@
+mod common;
+
+use std::{
+    net::{TcpListener, TcpStream},
+    process::{Child, Stdio},
+    thread,
+    time::Duration,
+};
+
+use assert_cmd::{Command, cargo::CommandCargoExt};
+use common::{asset_name, cache_fake_biome};
+
+/// Starts an installation that holds the lock on the installation directory
+/// until it is killed
+///
+/// The installation takes the lock before checking that the version exists,
+/// and the server never answers that request.
+fn start_blocked_installation(install_dir: &std::path::Path) -> (Child, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let server = format!("http://{}", listener.local_addr().unwrap());
+
+    let mut child = std::process::Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--version", "2.0.6", "--no-prepend-path"])
+        .args(["--no-cache", "--retries", "0"])
+        .args(["--download-mirror", &server, "--versions-mirror", &server])
+        .args(["--version-provider", "website"])
+        .arg("--install-dir")
+        .arg(install_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // Wait for the request, which is sent once the lock is held
+    listener.set_nonblocking(true).unwrap();
+
+    loop {
+        if let Ok((stream, _)) = listener.accept() {
+            return (child, stream);
+        }
+
+        assert!(child.try_wait().unwrap().is_none());
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[test]
+pub fn it_fails_when_another_installation_is_in_progress() {
+    let install_dir = assert_fs::TempDir::new().unwrap();
+    let (mut first, _request) = start_blocked_installation(install_dir.path());
+
+    let output = Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--version", "2.0.6", "--no-prepend-path"])
+        .arg("--install-dir")
+        .arg(install_dir.path())
+        .args(["--lock-timeout", "0", "--output", "json"])
+        .assert()
+        .code(10)
+        .get_output()
+        .clone();
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .arg("rollback")
+        .arg("--install-dir")
+        .arg(install_dir.path())
+        .args(["--lock-timeout", "0"])
+        .assert()
+        .code(10);
+
+    first.kill().unwrap();
+    first.wait().unwrap();
+
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+
+    assert_eq!(error["error"]["code"], "install_in_progress");
+    assert_eq!(
+        error["error"]["message"],
+        format!(
+            "Another installation into {} is still in progress after 0 seconds",
+            install_dir.path().display()
+        )
+    );
+
+    // The lock file is kept out of the installation directory
+    assert_eq!(std::fs::read_dir(install_dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+#[cfg(unix)]
+pub fn it_waits_for_another_installation_to_finish() {
+    let install_dir = assert_fs::TempDir::new().unwrap();
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+
+    cache_fake_biome(cache_dir.path(), "2.0.6", &asset_name());
+
+    let (mut first, _request) = start_blocked_installation(install_dir.path());
+
+    let mut second = std::process::Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args([
+            "install",
+            "--version",
+            "2.0.6",
+            "--no-prepend-path",
+            "--offline",
+        ])
+        .arg("--install-dir")
+        .arg(install_dir.path())
+        .arg("--cache-dir")
+        .arg(cache_dir.path())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    thread::sleep(Duration::from_secs(1));
+
+    assert!(second.try_wait().unwrap().is_none());
+    assert!(!install_dir.path().join("biome").exists());
+
+    first.kill().unwrap();
+    first.wait().unwrap();
+
+    assert!(second.wait().unwrap().success());
+    assert!(install_dir.path().join("biome").exists());
+}
@@ -0,0 +1,361 @@
+mod common;
+
+use assert_cmd::Command;
+use assert_fs::prelude::{FileWriteStr, PathChild};
+use common::{run_json, start_server};
+
+const LATEST: &str = "HTTP/1.1 200 OK\r\nContent-Length: 6\r\nConnection: close\r\n\r\n2.0.6\n";
+
+#[test]
+pub fn it_sends_requests_through_the_proxy() {
+    let (proxy, handle) = start_server([LATEST]);
+
+    let plan = run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--dry-run", "--non-interactive", "--no-cache"])
+            .args(["--versions-mirror", "http://versions.example.com/api"])
+            .args(["--proxy", &proxy]),
+    );
+
+    assert_eq!(plan["version"], "2.0.6");
+    assert_eq!(plan["version_source"], "latest");
+    assert!(
+        handle.join().unwrap()[0].starts_with("GET http://versions.example.com/api/latest.txt ")
+    );
+}
+
+#[test]
+pub fn it_retries_requests_that_fail_temporarily() {
+    let (server, handle) = start_server([
+        "HTTP/1.1 503 Service Unavailable\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        LATEST,
+    ]);
+
+    let plan = run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--dry-run", "--non-interactive", "--no-cache"])
+            .args(["--versions-mirror", &server]),
+    );
+    let requests = handle.join().unwrap();
+
+    assert_eq!(plan["version"], "2.0.6");
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].to_lowercase().contains(&format!(
+        "user-agent: biome-installer/{}",
+        env!("CARGO_PKG_VERSION")
+    )));
+}
+
+#[test]
+pub fn it_does_not_wait_for_longer_than_the_backoff() {
+    for (status, code) in [
+        ("429 Too Many Requests", 13),
+        ("503 Service Unavailable", 3),
+    ] {
+        let (server, handle) = start_server([format!(
+            "HTTP/1.1 {status}\r\nRetry-After: 3600\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        )]);
+
+        let started = std::time::Instant::now();
+
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--dry-run", "--non-interactive", "--no-cache"])
+            .args([
+                "--versions-mirror",
+                &server,
+                "--version-provider",
+                "website",
+            ])
+            .env_remove("BIOME_VERSION")
+            .assert()
+            .code(code);
+
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+        assert_eq!(handle.join().unwrap().len(), 1);
+    }
+}
+
+#[test]
+pub fn it_rejects_an_invalid_ca_certificate() {
+    let dir = assert_fs::TempDir::new().unwrap();
+    let ca_cert = dir.child("proxy.pem");
+    ca_cert
+        .write_str("-----BEGIN CERTIFICATE-----\nnot a certificate\n-----END CERTIFICATE-----\n")
+        .unwrap();
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--version", "2.0.6", "--dry-run"])
+        .arg("--ca-cert")
+        .arg(ca_cert.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Invalid CA certificate"));
+}
+
+#[test]
+pub fn it_never_sends_the_github_token_to_other_hosts() {
+    let (server, handle) = start_server([LATEST]);
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--dry-run", "--non-interactive", "--no-cache"])
+        .args(["--versions-mirror", &server])
+        .env("GITHUB_TOKEN", "secret-token")
+        .env_remove("BIOME_VERSION")
+        .assert()
+        .success();
+
+    assert!(!handle.join().unwrap()[0].contains("secret-token"));
+}
+
+#[test]
+pub fn it_reports_rate_limits() {
+    let (server, _) = start_server([
+        "HTTP/1.1 403 Forbidden\r\nx-ratelimit-limit: 60\r\nx-ratelimit-remaining: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+    ]);
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--dry-run", "--non-interactive", "--no-cache"])
+        .args([
+            "--versions-mirror",
+            &server,
+            "--version-provider",
+            "website",
+        ])
+        .env_remove("GITHUB_TOKEN")
+        .env_remove("GH_TOKEN")
+        .env_remove("BIOME_VERSION")
+        .assert()
+        .code(13)
+        .stderr(predicates::str::contains(
+            "all 60 requests allowed per hour have been used",
+        ))
+        .stderr(predicates::str::contains("--github-token"));
+}
+
+#[test]
+pub fn it_suggests_the_closest_version_with_a_build_for_the_platform() {
+    const NOT_FOUND: &str =
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    const FOUND: &str = "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    const VERSIONS: &str =
+        "HTTP/1.1 200 OK\r\nContent-Length: 18\r\nConnection: close\r\n\r\n1.2.0\n1.1.0\n1.0.0\n";
+
+    let install_dir = assert_fs::TempDir::new().unwrap();
+    let (server, handle) = start_server([NOT_FOUND, VERSIONS, FOUND, NOT_FOUND]);
+
+    let output = Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args([
+            "install",
+            "--version",
+            "1.0.0",
+            "--no-prepend-path",
+            "--no-cache",
+        ])
+        .args(["--download-mirror", &server, "--versions-mirror", &server])
+        .args(["--version-provider", "website"])
+        .arg("--install-dir")
+        .arg(install_dir.path())
+        .args(["--output", "json"])
+        .assert()
+        .code(14)
+        .get_output()
+        .clone();
+
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+
+    assert_eq!(error["error"]["code"], "asset_not_found");
+    assert!(
+        error["error"]["message"]
+            .as_str()
+            .unwrap()
+            .ends_with("the closest version that does is 1.2.0")
+    );
+
+    let requests = handle.join().unwrap();
+
+    assert!(requests[0].starts_with("HEAD /cli/v1.0.0/"));
+    assert!(requests[1].starts_with("GET /preview.txt "));
+    assert!(requests[2].starts_with("HEAD /cli/v1.2.0/"));
+    assert!(requests[3].starts_with("HEAD /cli/v1.1.0/"));
+}
+
+#[test]
+pub fn it_gives_up_on_requests_that_take_too_long() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let server = format!("http://{}", listener.local_addr().unwrap());
+
+    // Accept the connection, but never answer
+    std::thread::spawn(move || {
+        let _connection = listener.accept().unwrap();
+        std::thread::sleep(std::time::Duration::from_secs(30));
+    });
+
+    let started = std::time::Instant::now();
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--dry-run", "--non-interactive", "--no-cache"])
+        .args([
+            "--versions-mirror",
+            &server,
+            "--version-provider",
+            "website",
+        ])
+        .args(["--read-timeout", "1", "--retries", "0"])
+        .env_remove("BIOME_VERSION")
+        .assert()
+        .failure();
+
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+}
+
+#[test]
+pub fn it_reports_the_failure_to_fetch_the_list_of_versions() {
+    let (server, _) = start_server([
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+    ]);
+
+    let output = Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--version", "1.0.0", "--no-prepend-path"])
+        .args(["--no-cache", "--retries", "0"])
+        .args(["--download-mirror", &server, "--versions-mirror", &server])
+        .args(["--version-provider", "website", "--output", "json"])
+        .assert()
+        .code(3)
+        .get_output()
+        .clone();
+
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+
+    assert_eq!(error["error"]["code"], "network_error");
+}
+
+#[test]
+#[cfg(unix)]
+pub fn it_downloads_the_version_when_there_is_no_list_to_check_it_against() {
+    let install_dir = assert_fs::TempDir::new().unwrap();
+    let (server, handle) = start_server([
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".into(),
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".into(),
+        common::ok(b"#!/bin/sh\necho \"Version: 1.0.0\"\n"),
+    ]);
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--version", "1.0.0", "--no-prepend-path"])
+        .args(["--no-cache", "--retries", "0"])
+        .args(["--download-mirror", &server, "--versions-mirror", &server])
+        .args(["--version-provider", "website"])
+        .arg("--install-dir")
+        .arg(install_dir.path())
+        .assert()
+        .success();
+
+    let requests = handle.join().unwrap();
+
+    assert!(requests[1].starts_with("GET /preview.txt "));
+    assert!(requests[2].starts_with("GET /cli/v1.0.0/"));
+    assert!(install_dir.path().join("biome").exists());
+}
+
+/// Starts a server that answers the requests for the lists of versions with
+/// `404 Not Found`, and then sends the binary in the given chunks, pausing
+/// between each of them
+#[cfg(unix)]
+fn serve_in_chunks(chunks: &'static [&'static str], pause: std::time::Duration) -> String {
+    use std::io::{BufRead, BufReader, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let server = format!("http://{}", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for attempt in 0..3 {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut request = String::new();
+            while reader.read_line(&mut request).unwrap() > 0 && !request.ends_with("\r\n\r\n") {}
+
+            if attempt < 2 {
+                let _ = stream.write_all(
+                    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                );
+                continue;
+            }
+
+            let length: usize = chunks.iter().map(|chunk| chunk.len()).sum();
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {length}\r\nConnection: close\r\n\r\n"
+            );
+
+            for chunk in chunks {
+                std::thread::sleep(pause);
+
+                if stream.write_all(chunk.as_bytes()).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    server
+}
+
+#[test]
+#[cfg(unix)]
+pub fn it_does_not_interrupt_a_slow_download_that_makes_progress() {
+    let install_dir = assert_fs::TempDir::new().unwrap();
+    let server = serve_in_chunks(
+        &["#!/bin/sh\n", "echo \"Version: ", "1.0.0\"\n"],
+        std::time::Duration::from_millis(700),
+    );
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--version", "1.0.0", "--no-prepend-path"])
+        .args(["--no-cache", "--retries", "0", "--read-timeout", "1"])
+        .args(["--download-mirror", &server, "--versions-mirror", &server])
+        .args(["--version-provider", "website"])
+        .arg("--install-dir")
+        .arg(install_dir.path())
+        .assert()
+        .success();
+
+    assert!(install_dir.path().join("biome").exists());
+}
+
+#[test]
+#[cfg(unix)]
+pub fn it_gives_up_on_a_download_that_stalls() {
+    let install_dir = assert_fs::TempDir::new().unwrap();
+    let server = serve_in_chunks(
+        &["#!/bin/sh\n", "echo \"Version: 1.0.0\"\n"],
+        std::time::Duration::from_secs(5),
+    );
+
+    let started = std::time::Instant::now();
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--version", "1.0.0", "--no-prepend-path"])
+        .args(["--no-cache", "--retries", "0", "--read-timeout", "1"])
+        .args(["--download-mirror", &server, "--versions-mirror", &server])
+        .args(["--version-provider", "website"])
+        .arg("--install-dir")
+        .arg(install_dir.path())
+        .assert()
+        .code(3);
+
+    assert!(started.elapsed() < std::time::Duration::from_secs(4));
+    assert!(!install_dir.path().join("biome").exists());
+}
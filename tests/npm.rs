@@ -0,0 +1,147 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::net::TcpListener;
+
+use assert_cmd::Command;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use common::{asset_name, ok, run_json, serve};
+use flate2::{Compression, write::GzEncoder};
+use sha2::{Digest, Sha512};
+
+/// Returns the name of the npm package for the current platform
+fn package_name() -> String {
+    format!("@biomejs/cli-{}", asset_name().trim_start_matches("biome-"))
+}
+
+/// Builds the tarball of an npm package holding a fake Biome binary
+fn package_tarball(version: &str) -> Vec<u8> {
+    let content = format!("#!/bin/sh\necho \"Version: {version}\"\n");
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o755);
+    header.set_cksum();
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    builder
+        .append_data(&mut header, "package/biome", content.as_bytes())
+        .unwrap();
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+#[test]
+pub fn it_installs_biome_from_an_npm_registry() {
+    let work_dir = assert_fs::TempDir::new().unwrap();
+    let install_dir = work_dir.path().join("bin");
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let registry = format!("http://{}", listener.local_addr().unwrap());
+
+    let package = package_name();
+    let tarball = package_tarball("2.0.6");
+    let tarball_url = format!("{registry}/tarballs/biome-2.0.6.tgz");
+    let packument = serde_json::json!({
+        "name": package,
+        "dist-tags": { "latest": "2.0.6" },
+        "versions": {
+            "2.0.6": {
+                "dist": {
+                    "tarball": tarball_url,
+                    "integrity": format!("sha512-{}", STANDARD.encode(Sha512::digest(&tarball))),
+                }
+            }
+        }
+    });
+
+    let packument = ok(packument.to_string().as_bytes());
+    let handle = serve(listener, vec![packument.clone(), packument, ok(&tarball)]);
+
+    let report = run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--version", "2.0.6"])
+            .args(["--no-prepend-path", "--no-cache"])
+            .args(["--source", "npm", "--npm-registry", &registry])
+            .arg("--install-dir")
+            .arg(&install_dir),
+    );
+
+    assert_eq!(report["version"], "2.0.6");
+    assert!(install_dir.join("biome").exists());
+
+    let requests = handle.join().unwrap();
+
+    assert!(requests[1].starts_with(&format!("GET /{} ", package.replace('/', "%2f"))));
+    assert!(requests[2].starts_with("GET /tarballs/biome-2.0.6.tgz "));
+}
+
+#[test]
+pub fn it_rejects_npm_packages_that_fail_the_integrity_check() {
+    let work_dir = assert_fs::TempDir::new().unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let registry = format!("http://{}", listener.local_addr().unwrap());
+
+    let tarball = package_tarball("2.0.6");
+    let packument = serde_json::json!({
+        "versions": {
+            "2.0.6": {
+                "dist": {
+                    "tarball": format!("{registry}/biome-2.0.6.tgz"),
+                    "integrity": format!("sha512-{}", STANDARD.encode(Sha512::digest(b"tampered"))),
+                }
+            }
+        }
+    });
+
+    let packument = ok(packument.to_string().as_bytes());
+    let _handle = serve(listener, vec![packument.clone(), packument, ok(&tarball)]);
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--version", "2.0.6"])
+        .args(["--no-prepend-path", "--no-cache"])
+        .args(["--source", "npm", "--npm-registry", &registry])
+        .arg("--install-dir")
+        .arg(work_dir.path().join("bin"))
+        .assert()
+        .code(6);
+
+    assert!(!work_dir.path().join("bin").join("biome").exists());
+}
+
+#[test]
+pub fn it_resolves_the_latest_version_from_the_npm_registry() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let registry = format!("http://{}", listener.local_addr().unwrap());
+
+    let packument = serde_json::json!({
+        "dist-tags": { "latest": "2.0.6" },
+        "versions": {
+            "2.0.5": { "dist": { "tarball": format!("{registry}/biome-2.0.5.tgz") } },
+            "2.0.6": { "dist": { "tarball": format!("{registry}/biome-2.0.6.tgz") } },
+        }
+    });
+
+    let handle = serve(listener, vec![ok(packument.to_string().as_bytes())]);
+
+    let plan = run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--dry-run", "--non-interactive", "--no-cache"])
+            .args(["--source", "npm", "--npm-registry", &registry]),
+    );
+
+    assert_eq!(plan["version"], "2.0.6");
+    assert_eq!(
+        plan["asset_url"],
+        format!(
+            "{registry}/{}/-/{}-2.0.6.tgz",
+            package_name(),
+            package_name().trim_start_matches("@biomejs/")
+        )
+    );
+    assert!(handle.join().unwrap()[0].starts_with("GET /@biomejs%2fbiome "));
+}
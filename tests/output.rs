@@ -0,0 +1,20 @@
+use assert_cmd::Command;
+
+#[test]
+pub fn it_reports_invalid_arguments_as_json() {
+    let output = Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--unknown", "--output", "json"])
+        .assert()
+        .code(2)
+        .get_output()
+        .clone();
+
+    let document: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+
+    assert_eq!(document["error"]["code"], "invalid_arguments");
+    assert_eq!(
+        document["error"]["message"],
+        "unexpected argument '--unknown' found"
+    );
+}
@@ -0,0 +1,372 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::path::Path;
+
+use assert_cmd::Command;
+use assert_fs::prelude::{FileWriteStr, PathChild};
+use common::{asset_name, cache_fake_biome, run_json};
+
+fn install(home: &Path, cache_dir: &Path, install_dir: &Path, shell: &str) -> serde_json::Value {
+    run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--version", "2.0.6"])
+            .arg("--install-dir")
+            .arg(install_dir)
+            .arg("--cache-dir")
+            .arg(cache_dir)
+            .env("HOME", home)
+            .env("SHELL", shell)
+            .env_remove("PATH"),
+    )
+}
+
+fn remove(home: &Path, args: &[&str]) -> serde_json::Value {
+    run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["path", "remove"])
+            .args(args)
+            .env("HOME", home),
+    )
+}
+
+#[test]
+pub fn it_replaces_the_block_managed_by_the_installer_in_place() {
+    let home = assert_fs::TempDir::new().unwrap();
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    cache_fake_biome(cache_dir.path(), "2.0.6", &asset_name());
+
+    let zshrc = home.child(".zshrc");
+    zshrc.write_str("alias ll='ls -l'").unwrap();
+
+    let first = home.path().join("first");
+    let report = install(home.path(), cache_dir.path(), &first, "/bin/zsh");
+
+    assert_eq!(report["path_update"], "success");
+    assert_eq!(
+        report["path_updates"],
+        serde_json::json!([{ "shell": "zsh", "outcome": "success" }])
+    );
+
+    let block = |dir: &Path| {
+        format!(
+            "# >>> biome-installer >>>\nexport PATH=\"{}:$PATH\"\n# <<< biome-installer <<<\n",
+            dir.display()
+        )
+    };
+
+    assert_eq!(
+        std::fs::read_to_string(zshrc.path()).unwrap(),
+        format!("alias ll='ls -l'\n\n{}", block(&first))
+    );
+
+    // Installing into the same directory again leaves the file untouched
+    let report = install(home.path(), cache_dir.path(), &first, "/bin/zsh");
+
+    assert_eq!(report["path_update"], "already_in_path");
+
+    // Lines added after the block are kept when it is replaced
+    let mut content = std::fs::read_to_string(zshrc.path()).unwrap();
+    content.push_str("export EDITOR=vim");
+    std::fs::write(zshrc.path(), content).unwrap();
+
+    let second = home.path().join("second");
+    install(home.path(), cache_dir.path(), &second, "/bin/zsh");
+
+    assert_eq!(
+        std::fs::read_to_string(zshrc.path()).unwrap(),
+        format!("alias ll='ls -l'\n\n{}export EDITOR=vim", block(&second))
+    );
+
+    // The block is removed as a whole
+    let report = remove(home.path(), &[]);
+
+    assert_eq!(
+        report["removed"][0]["lines"][0],
+        format!("export PATH=\"{}:$PATH\"", second.display())
+    );
+    assert_eq!(
+        std::fs::read_to_string(zshrc.path()).unwrap(),
+        "alias ll='ls -l'\nexport EDITOR=vim"
+    );
+}
+
+#[test]
+pub fn it_migrates_the_lines_written_by_previous_versions() {
+    let home = assert_fs::TempDir::new().unwrap();
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    cache_fake_biome(cache_dir.path(), "2.0.6", &asset_name());
+
+    let install_dir = home.path().join("bin");
+
+    let zshrc = home.child(".zshrc");
+    zshrc
+        .write_str(&format!(
+            "alias ll='ls -l'\n\n\
+             # Biome installation dir\n\
+             export PATH=\"{}:$PATH\"\n\
+             export EDITOR=vim\n",
+            install_dir.display()
+        ))
+        .unwrap();
+
+    let report = install(home.path(), cache_dir.path(), &install_dir, "/bin/zsh");
+
+    assert_eq!(report["path_update"], "success");
+    assert_eq!(
+        std::fs::read_to_string(zshrc.path()).unwrap(),
+        format!(
+            "alias ll='ls -l'\n\n\
+             # >>> biome-installer >>>\n\
+             export PATH=\"{}:$PATH\"\n\
+             # <<< biome-installer <<<\n\
+             export EDITOR=vim\n",
+            install_dir.display()
+        )
+    );
+}
+
+#[test]
+pub fn it_reports_a_shell_configuration_file_it_cannot_update() {
+    let home = assert_fs::TempDir::new().unwrap();
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    cache_fake_biome(cache_dir.path(), "2.0.6", &asset_name());
+
+    let zshrc = home.child(".zshrc");
+    zshrc
+        .write_str("# >>> biome-installer >>>\nexport PATH=\"/old/bin:$PATH\"\n")
+        .unwrap();
+
+    let output = Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--version", "2.0.6"])
+        .arg("--install-dir")
+        .arg(home.path().join("bin"))
+        .arg("--cache-dir")
+        .arg(cache_dir.path())
+        .args(["--output", "json"])
+        .env("HOME", home.path())
+        .env_remove("ZDOTDIR")
+        .env("SHELL", "/bin/zsh")
+        .assert()
+        .code(8)
+        .get_output()
+        .clone();
+
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+
+    assert_eq!(error["error"]["code"], "path_update_failed");
+    assert!(
+        error["error"]["causes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|cause| cause.as_str().unwrap().contains("is not closed"))
+    );
+}
+
+#[test]
+pub fn it_updates_the_other_shells_when_one_cannot_be_updated() {
+    let home = assert_fs::TempDir::new().unwrap();
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    cache_fake_biome(cache_dir.path(), "2.0.6", &asset_name());
+
+    let install_dir = home.path().join("bin");
+
+    home.child(".zshrc")
+        .write_str("# >>> biome-installer >>>\nexport PATH=\"/old/bin:$PATH\"\n")
+        .unwrap();
+    home.child(".bashrc").write_str("").unwrap();
+
+    let output = Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--version", "2.0.6", "--shells", "zsh,bash"])
+        .arg("--install-dir")
+        .arg(&install_dir)
+        .arg("--cache-dir")
+        .arg(cache_dir.path())
+        .args(["--output", "json"])
+        .env("HOME", home.path())
+        .env_remove("ZDOTDIR")
+        .assert()
+        .code(8)
+        .get_output()
+        .clone();
+
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    let causes = error["error"]["causes"].as_array().unwrap();
+
+    assert_eq!(error["error"]["code"], "path_update_failed");
+    assert!(
+        causes[0]
+            .as_str()
+            .unwrap()
+            .starts_with("Could not update the PATH of zsh: ")
+    );
+    assert_eq!(causes.len(), 1);
+    assert!(
+        std::fs::read_to_string(home.path().join(".bashrc"))
+            .unwrap()
+            .contains(&format!("export PATH=\"{}:$PATH\"", install_dir.display()))
+    );
+}
+
+#[test]
+pub fn it_uses_the_path_syntax_of_each_shell() {
+    let home = assert_fs::TempDir::new().unwrap();
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    cache_fake_biome(cache_dir.path(), "2.0.6", &asset_name());
+
+    let install_dir = home.path().join("bin");
+    let dir = install_dir.display();
+
+    let nushell_dir = match std::env::consts::OS {
+        "macos" => "Library/Application Support/nushell",
+        _ => ".config/nushell",
+    };
+
+    let cases = [
+        (
+            "/usr/bin/nu",
+            format!("{nushell_dir}/env.nu"),
+            format!("$env.PATH = ($env.PATH | prepend \"{dir}\")"),
+        ),
+        (
+            "/usr/bin/elvish",
+            ".config/elvish/rc.elv".to_string(),
+            format!("set paths = [\"{dir}\" $@paths]"),
+        ),
+        (
+            "/usr/bin/xonsh",
+            ".xonshrc".to_string(),
+            format!("$PATH.insert(0, \"{dir}\")"),
+        ),
+        (
+            "/bin/tcsh",
+            ".tcshrc".to_string(),
+            format!("set path = (\"{dir}\" $path)"),
+        ),
+        (
+            "/usr/bin/pwsh",
+            ".config/powershell/Microsoft.PowerShell_profile.ps1".to_string(),
+            format!("$env:PATH = \"{dir}:$env:PATH\""),
+        ),
+    ];
+
+    for (shell, file, line) in cases {
+        let config = home.child(&file);
+        config.write_str("").unwrap();
+
+        install(home.path(), cache_dir.path(), &install_dir, shell);
+
+        assert!(
+            std::fs::read_to_string(config.path())
+                .unwrap()
+                .contains(&format!("\n{line}\n")),
+            "{shell} does not prepend to the PATH in {file}"
+        );
+    }
+
+    let report = remove(home.path(), &[]);
+
+    assert_eq!(report["removed"].as_array().unwrap().len(), 5);
+}
+
+#[test]
+pub fn it_updates_the_path_of_the_selected_shells() {
+    let home = assert_fs::TempDir::new().unwrap();
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+    cache_fake_biome(cache_dir.path(), "2.0.6", &asset_name());
+
+    let bashrc = home.child(".bashrc");
+    let zshrc = home.child(".zshrc");
+    let fish_config = home.child(".config/fish/config.fish");
+
+    for file in [&bashrc, &zshrc, &fish_config] {
+        file.write_str("").unwrap();
+    }
+
+    let install_dir = home.path().join("bin");
+
+    let report = run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--version", "2.0.6", "--shells", "zsh,fish"])
+            .arg("--install-dir")
+            .arg(&install_dir)
+            .arg("--cache-dir")
+            .arg(cache_dir.path())
+            .env("HOME", home.path())
+            .env("SHELL", "/bin/bash")
+            .env_remove("PATH"),
+    );
+
+    assert_eq!(report["path_update"], "success");
+    assert_eq!(std::fs::read_to_string(bashrc.path()).unwrap(), "");
+    assert!(
+        std::fs::read_to_string(zshrc.path())
+            .unwrap()
+            .contains(&format!("export PATH=\"{}:$PATH\"", install_dir.display()))
+    );
+    assert!(
+        std::fs::read_to_string(fish_config.path())
+            .unwrap()
+            .contains(&format!("set -gx PATH \"{}\" $PATH", install_dir.display()))
+    );
+}
+
+#[test]
+pub fn it_removes_the_path_entries_written_by_the_installer() {
+    let home = assert_fs::TempDir::new().unwrap();
+
+    let zshrc = home.child(".zshrc");
+    zshrc
+        .write_str(
+            "alias ll='ls -l'\n\
+             \n\
+             # Biome installation dir\n\
+             export PATH=\"/old/.biome/bin:$PATH\"\n\
+             export PATH=\"/opt/tools/bin:$PATH\"\n\
+             \n\
+             # Biome installation dir\n\
+             export PATH=\"/home/user/.biome/bin:$PATH\"",
+        )
+        .unwrap();
+
+    let fish_config = home.child(".config/fish/config.fish");
+    fish_config
+        .write_str("set -gx EDITOR vim\n\n# Biome installation dir\nset -gx PATH \"/old/.biome/bin\" $PATH")
+        .unwrap();
+
+    let report = remove(home.path(), &[]);
+
+    assert_eq!(report["removed"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        std::fs::read_to_string(zshrc.path()).unwrap(),
+        "alias ll='ls -l'\nexport PATH=\"/opt/tools/bin:$PATH\"\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(fish_config.path()).unwrap(),
+        "set -gx EDITOR vim\n"
+    );
+    assert!(
+        std::fs::read_to_string(home.child(".zshrc.biome-installer.bak").path())
+            .unwrap()
+            .contains("/home/user/.biome/bin")
+    );
+
+    // Lines not written by the installer are only removed for a given directory
+    let report = remove(home.path(), &["--install-dir", "/opt/tools/bin"]);
+
+    assert_eq!(
+        report["removed"][0]["lines"][0],
+        "export PATH=\"/opt/tools/bin:$PATH\""
+    );
+    assert_eq!(
+        std::fs::read_to_string(zshrc.path()).unwrap(),
+        "alias ll='ls -l'\n"
+    );
+}
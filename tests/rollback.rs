@@ -0,0 +1,140 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{os::unix::fs::PermissionsExt, path::Path};
+
+use assert_cmd::Command;
+use common::{asset_name, cache_binary};
+
+/// Writes a fake Biome binary that reports the given version
+fn fake_biome(path: &Path, version: &str) {
+    std::fs::write(path, format!("#!/bin/sh\necho \"Version: {version}\"\n")).unwrap();
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+fn reported_version(path: &Path) -> String {
+    String::from_utf8(
+        std::process::Command::new(path)
+            .arg("--version")
+            .output()
+            .unwrap()
+            .stdout,
+    )
+    .unwrap()
+}
+
+#[test]
+pub fn it_swaps_the_current_binary_with_the_backup() {
+    let install_dir = assert_fs::TempDir::new().unwrap();
+
+    fake_biome(&install_dir.path().join("biome"), "2.0.6");
+    std::fs::create_dir(install_dir.path().join(".biome-backup")).unwrap();
+    fake_biome(&install_dir.path().join(".biome-backup/biome"), "2.0.5");
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .arg("rollback")
+        .arg("--install-dir")
+        .arg(install_dir.path())
+        .assert()
+        .success();
+
+    assert!(reported_version(&install_dir.path().join("biome")).contains("2.0.5"));
+    assert!(reported_version(&install_dir.path().join(".biome-backup/biome")).contains("2.0.6"));
+}
+
+#[test]
+pub fn it_fails_when_there_is_no_backup() {
+    let install_dir = assert_fs::TempDir::new().unwrap();
+
+    fake_biome(&install_dir.path().join("biome"), "2.0.6");
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .arg("rollback")
+        .arg("--install-dir")
+        .arg(install_dir.path())
+        .assert()
+        .failure();
+
+    assert!(reported_version(&install_dir.path().join("biome")).contains("2.0.6"));
+}
+
+#[test]
+pub fn it_restores_the_previous_binary_when_the_new_one_does_not_work() {
+    let install_dir = assert_fs::TempDir::new().unwrap();
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+
+    fake_biome(&install_dir.path().join("biome"), "2.0.5");
+    cache_binary(
+        cache_dir.path(),
+        "2.0.6",
+        &asset_name(),
+        "#!/bin/sh\nexit 1\n",
+    );
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args([
+            "install",
+            "--version",
+            "2.0.6",
+            "--offline",
+            "--no-prepend-path",
+        ])
+        .arg("--install-dir")
+        .arg(install_dir.path())
+        .arg("--cache-dir")
+        .arg(cache_dir.path())
+        .assert()
+        .code(9);
+
+    assert!(reported_version(&install_dir.path().join("biome")).contains("2.0.5"));
+    assert!(!install_dir.path().join(".biome-backup/biome").exists());
+}
+
+#[test]
+pub fn it_keeps_the_backup_when_an_upgrade_fails() {
+    let install_dir = assert_fs::TempDir::new().unwrap();
+    let cache_dir = assert_fs::TempDir::new().unwrap();
+
+    common::cache_fake_biome(cache_dir.path(), "2.0.6", &asset_name());
+    common::cache_fake_biome(cache_dir.path(), "2.0.7", &asset_name());
+    cache_binary(
+        cache_dir.path(),
+        "2.0.8",
+        &asset_name(),
+        "#!/bin/sh\nexit 1\n",
+    );
+
+    let install = |version: &str| {
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--offline", "--no-prepend-path"])
+            .args(["--version", version])
+            .arg("--install-dir")
+            .arg(install_dir.path())
+            .arg("--cache-dir")
+            .arg(cache_dir.path())
+            .assert()
+    };
+
+    install("2.0.6").success();
+    install("2.0.7").success();
+    install("2.0.8").code(9);
+
+    assert!(reported_version(&install_dir.path().join("biome")).contains("2.0.7"));
+    assert!(reported_version(&install_dir.path().join(".biome-backup/biome")).contains("2.0.6"));
+
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .arg("rollback")
+        .arg("--install-dir")
+        .arg(install_dir.path())
+        .assert()
+        .success();
+
+    assert!(reported_version(&install_dir.path().join("biome")).contains("2.0.6"));
+    assert!(reported_version(&install_dir.path().join(".biome-backup/biome")).contains("2.0.7"));
+}
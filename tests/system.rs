@@ -0,0 +1,82 @@
+#![cfg(unix)]
+
+mod common;
+
+use assert_cmd::Command;
+use common::run_json;
+
+fn system_plan(args: &[&str]) -> serde_json::Value {
+    run_json(
+        Command::cargo_bin("biome-installer")
+            .unwrap()
+            .args(["install", "--version", "2.0.6", "--dry-run", "--system"])
+            .args(args),
+    )
+}
+
+#[test]
+pub fn it_installs_biome_for_every_user_into_usr_local_bin() {
+    let plan = system_plan(&[]);
+
+    assert_eq!(plan["destination"], "/usr/local/bin/biome");
+    assert_eq!(plan["path_update"], serde_json::Value::Null);
+}
+
+#[test]
+pub fn it_ignores_the_installation_directory_of_the_current_user() {
+    let dir = assert_fs::TempDir::new().unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(&config, "install_dir = \"/home/user/.biome/bin\"\n").unwrap();
+
+    // From the environment, then from the configuration file
+    for env in [Some("/home/user/bin"), None] {
+        let mut command = Command::cargo_bin("biome-installer").unwrap();
+
+        if let Some(dir) = env {
+            command.env("BIOME_INSTALL_DIR", dir);
+        }
+
+        let plan = run_json(
+            command
+                .args(["install", "--version", "2.0.6", "--dry-run", "--system"])
+                .env("BIOME_CONFIG", &config),
+        );
+
+        assert_eq!(plan["destination"], "/usr/local/bin/biome");
+    }
+}
+
+#[test]
+pub fn it_adds_a_custom_system_directory_to_the_system_profile() {
+    let plan = system_plan(&["--install-dir", "/opt/biome/bin"]);
+
+    assert_eq!(plan["destination"], "/opt/biome/bin/biome");
+
+    match std::env::consts::OS {
+        "macos" => {
+            assert_eq!(plan["path_update"]["target"], "/etc/paths.d/biome");
+            assert_eq!(plan["path_update"]["line"], "/opt/biome/bin");
+        }
+        _ => {
+            assert_eq!(plan["path_update"]["target"], "/etc/profile.d/biome.sh");
+            assert_eq!(
+                plan["path_update"]["line"],
+                "export PATH=\"/opt/biome/bin:$PATH\""
+            );
+        }
+    }
+
+    // A link from /usr/local/bin makes the system profile unnecessary
+    let plan = system_plan(&["--install-dir", "/opt/biome/bin", "--symlink"]);
+
+    assert_eq!(plan["path_update"], serde_json::Value::Null);
+}
+
+#[test]
+pub fn it_only_links_biome_with_a_system_installation() {
+    Command::cargo_bin("biome-installer")
+        .unwrap()
+        .args(["install", "--version", "2.0.6", "--dry-run", "--symlink"])
+        .assert()
+        .code(2);
+}